@@ -0,0 +1,69 @@
+use crate::parallel::par_for_each_pixel;
+use image::{DynamicImage, ImageBuffer, Luma, Rgb, Rgba};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum AlphaMode {
+    Discard,
+    Keep,
+    Composite,
+}
+
+pub fn extract_alpha(image: &DynamicImage) -> ImageBuffer<Luma<f32>, Vec<f32>> {
+    let rgba_image = image.to_rgba32f();
+
+    return ImageBuffer::from_fn(rgba_image.width(), rgba_image.height(), |x, y| {
+        Luma([rgba_image.get_pixel(x, y)[3]])
+    });
+}
+
+pub fn composite(
+    image: &mut ImageBuffer<Rgb<f32>, Vec<f32>>,
+    alpha: &ImageBuffer<Luma<f32>, Vec<f32>>,
+    background: Rgb<f32>,
+) {
+    par_for_each_pixel(image, |x, y, pixel| {
+        let coverage = alpha.get_pixel(x, y)[0];
+
+        *pixel =
+            Rgb([0, 1, 2]
+                .map(|index| pixel[index] * coverage + background[index] * (1.0 - coverage)));
+    });
+}
+
+/// Scales color by coverage, so that blurring and resampling don't pull in the color of
+/// transparent pixels.
+pub fn premultiply(
+    image: &mut ImageBuffer<Rgb<f32>, Vec<f32>>,
+    alpha: &ImageBuffer<Luma<f32>, Vec<f32>>,
+) {
+    composite(image, alpha, Rgb([0.0, 0.0, 0.0]));
+}
+
+pub fn unpremultiply(
+    image: &mut ImageBuffer<Rgb<f32>, Vec<f32>>,
+    alpha: &ImageBuffer<Luma<f32>, Vec<f32>>,
+) {
+    par_for_each_pixel(image, |x, y, pixel| {
+        let coverage = alpha.get_pixel(x, y)[0];
+
+        *pixel = if coverage > 0.0 {
+            Rgb(pixel.0.map(|channel| channel / coverage.min(1.0)))
+        } else {
+            Rgb([0.0, 0.0, 0.0])
+        };
+    });
+}
+
+pub fn attach_alpha(
+    image: &ImageBuffer<Rgb<f32>, Vec<f32>>,
+    alpha: &ImageBuffer<Luma<f32>, Vec<f32>>,
+) -> ImageBuffer<Rgba<f32>, Vec<f32>> {
+    return ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+        let [red, green, blue] = image.get_pixel(x, y).0;
+
+        Rgba([red, green, blue, alpha.get_pixel(x, y)[0].clamp(0.0, 1.0)])
+    });
+}
@@ -0,0 +1,195 @@
+use crate::{
+    error::CrtError,
+    output::{Encoding, OutputFormat, PngCompression},
+};
+use image::{
+    codecs::{
+        gif::{GifDecoder, GifEncoder, Repeat},
+        png::PngDecoder,
+        webp::WebPDecoder,
+    },
+    error::{EncodingError, ImageFormatHint, ParameterError, ParameterErrorKind, UnsupportedError},
+    AnimationDecoder, Delay, Frame, ImageError, ImageFormat,
+};
+use std::io::{Cursor, Write};
+
+/// Decodes every frame of an animated GIF, PNG or WebP, or returns `None` for a still image.
+pub fn decode_animation(bytes: &[u8], format: ImageFormat) -> Result<Option<Vec<Frame>>, CrtError> {
+    let frames = match format {
+        ImageFormat::Gif => GifDecoder::new(Cursor::new(bytes))
+            .map_err(CrtError::decode)?
+            .into_frames()
+            .collect_frames(),
+        ImageFormat::Png => {
+            let decoder = PngDecoder::new(Cursor::new(bytes)).map_err(CrtError::decode)?;
+
+            if !decoder.is_apng() {
+                return Ok(None);
+            }
+
+            decoder.apng().into_frames().collect_frames()
+        }
+        ImageFormat::WebP => {
+            let decoder = WebPDecoder::new(Cursor::new(bytes)).map_err(CrtError::decode)?;
+
+            if !decoder.has_animation() {
+                return Ok(None);
+            }
+
+            decoder.into_frames().collect_frames()
+        }
+        _ => return Ok(None),
+    }
+    .map_err(CrtError::decode)?;
+
+    if frames.len() < 2 {
+        return Ok(None);
+    }
+
+    return Ok(Some(frames));
+}
+
+/// APNG stores delays as 16-bit fractions of a second; longer ones are rounded to milliseconds.
+fn apng_delay(delay: Delay) -> (u16, u16) {
+    let (numerator, denominator) = delay.numer_denom_ms();
+
+    match (
+        u16::try_from(numerator),
+        u16::try_from(denominator as u64 * 1000),
+    ) {
+        (Ok(numerator), Ok(denominator)) => return (numerator, denominator),
+        _ => {
+            let milliseconds = (numerator as f64 / denominator as f64).round();
+
+            return (milliseconds.min(u16::MAX as f64) as u16, 1000);
+        }
+    }
+}
+
+fn write_apng<W: Write>(
+    writer: W,
+    frames: Vec<Frame>,
+    compression: PngCompression,
+) -> Result<(), png::EncodingError> {
+    let (res_x, res_y) = frames[0].buffer().dimensions();
+    let mut encoder = png::Encoder::new(writer, res_x, res_y);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_compression(match compression {
+        PngCompression::Fast => png::Compression::Fast,
+        PngCompression::Balanced => png::Compression::Default,
+        PngCompression::Best => png::Compression::Best,
+    });
+    encoder.set_animated(frames.len() as u32, 0)?;
+
+    let mut writer = encoder.write_header()?;
+
+    for frame in frames {
+        let (numerator, denominator) = apng_delay(frame.delay());
+
+        writer.set_frame_delay(numerator, denominator)?;
+        writer.write_image_data(frame.buffer().as_raw())?;
+    }
+
+    return writer.finish();
+}
+
+pub fn write_animation<W: Write>(
+    writer: W,
+    frames: Vec<Frame>,
+    encoding: &Encoding,
+) -> Result<(), CrtError> {
+    if frames.is_empty() {
+        return Err(CrtError::Encode(ImageError::Parameter(
+            ParameterError::from_kind(ParameterErrorKind::Generic(
+                "an animation needs at least one frame".to_string(),
+            )),
+        )));
+    }
+
+    match encoding.format {
+        OutputFormat::Gif => {
+            let mut encoder = GifEncoder::new(writer);
+            encoder
+                .set_repeat(Repeat::Infinite)
+                .map_err(CrtError::encode)?;
+            encoder.encode_frames(frames).map_err(CrtError::encode)?;
+        }
+        OutputFormat::Png => {
+            write_apng(writer, frames, encoding.png_compression).map_err(|error| {
+                CrtError::encode(ImageError::Encoding(EncodingError::new(
+                    ImageFormatHint::Exact(ImageFormat::Png),
+                    error,
+                )))
+            })?
+        }
+        format => {
+            return Err(CrtError::Encode(ImageError::Unsupported(
+                UnsupportedError::from(ImageFormatHint::Name(format.extension().to_string())),
+            )))
+        }
+    }
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+    use std::time::Duration;
+
+    #[test]
+    fn apng_delays_keep_their_length() {
+        assert_eq!(apng_delay(Delay::from_numer_denom_ms(100, 1)), (100, 1000));
+        assert_eq!(apng_delay(Delay::from_numer_denom_ms(50, 3)), (50, 3000));
+        assert_eq!(
+            apng_delay(Delay::from_numer_denom_ms(100000, 1000)),
+            (100, 1000)
+        );
+        assert_eq!(
+            apng_delay(Delay::from_numer_denom_ms(70000, 1)),
+            (65535, 1000)
+        );
+    }
+
+    #[test]
+    fn apng_round_trips_frames_and_delays() {
+        let frames = (1..=3)
+            .map(|index| {
+                Frame::from_parts(
+                    RgbaImage::from_pixel(4, 3, Rgba([index as u8 * 80, 0, 0, 255])),
+                    0,
+                    0,
+                    Delay::from_numer_denom_ms(index * 100, 1),
+                )
+            })
+            .collect::<Vec<Frame>>();
+        let mut bytes = Vec::new();
+        write_apng(&mut bytes, frames, PngCompression::Fast).unwrap();
+
+        let decoded = decode_animation(&bytes, ImageFormat::Png).unwrap().unwrap();
+        let delays = decoded
+            .iter()
+            .map(|frame| Duration::from(frame.delay()).as_millis())
+            .collect::<Vec<u128>>();
+
+        assert_eq!(delays, [100, 200, 300]);
+        assert_eq!(decoded[2].buffer().get_pixel(1, 1), &Rgba([240, 0, 0, 255]));
+    }
+
+    #[test]
+    fn empty_animations_are_an_error() {
+        let encoding = Encoding {
+            format: OutputFormat::Png,
+            quality: 90,
+            png_compression: PngCompression::Fast,
+            bit_depth: None,
+        };
+
+        assert!(matches!(
+            write_animation(Vec::new(), Vec::new(), &encoding),
+            Err(CrtError::Encode(_))
+        ));
+    }
+}
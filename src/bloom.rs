@@ -0,0 +1,48 @@
+use crate::{
+    parallel::par_for_each_pixel,
+    resample::{par_blur, par_resize},
+};
+use image::{imageops::FilterType, ImageBuffer, Luma, Rgb};
+
+const DOWNSAMPLING: u32 = 4;
+
+pub fn apply_bloom(
+    image: &mut ImageBuffer<Rgb<f32>, Vec<f32>>,
+    source: &ImageBuffer<Rgb<f32>, Vec<f32>>,
+    coverage: &ImageBuffer<Luma<f32>, Vec<f32>>,
+    threshold: f32,
+    radius: f32,
+    intensity: f32,
+) {
+    let (res_x, res_y) = image.dimensions();
+    let knee = (1.0 - threshold).max(f32::EPSILON);
+    let mut highlights = source.clone();
+
+    par_for_each_pixel(&mut highlights, |_, _, pixel| {
+        let luma = 0.2126 * pixel[0] + 0.7152 * pixel[1] + 0.0722 * pixel[2];
+        let weight = ((luma - threshold) / knee).clamp(0.0, 1.0);
+
+        *pixel = Rgb(pixel.0.map(|channel| channel * weight));
+    });
+
+    // The glow is low frequency, so it is blurred at reduced resolution to keep large radii cheap.
+    let small = par_resize(
+        &highlights,
+        (res_x / DOWNSAMPLING).max(1),
+        (res_y / DOWNSAMPLING).max(1),
+        FilterType::Triangle,
+    );
+    let glow = par_resize(
+        &par_blur(&small, radius / DOWNSAMPLING as f32),
+        res_x,
+        res_y,
+        FilterType::Triangle,
+    );
+
+    par_for_each_pixel(image, |x, y, pixel| {
+        let light = glow.get_pixel(x, y);
+        let strength = intensity * coverage.get_pixel(x, y)[0];
+
+        *pixel = Rgb([0, 1, 2].map(|index| pixel[index] + light[index] * strength));
+    });
+}
@@ -0,0 +1,222 @@
+use crate::{
+    alpha::AlphaMode,
+    mask::MaskType,
+    pipeline::StageKind,
+    preset::Preset,
+    scanlines::{BeamProfile, ScanlineMode, Scanlines},
+    settings::{CrtSettings, Look, SettingsError},
+};
+use image::Rgb;
+
+/// Builds [`CrtSettings`] with named setters. Anything left unset gets the same default as on
+/// the command line, except that scanlines default to `auto` and brightness and contrast to 0.
+#[derive(Clone, Default)]
+pub struct CrtSettingsBuilder {
+    look: Look,
+}
+
+impl CrtSettings {
+    pub fn builder() -> CrtSettingsBuilder {
+        return CrtSettingsBuilder::default();
+    }
+}
+
+impl CrtSettingsBuilder {
+    /// Fills every value not set explicitly, before or after this call, from a preset.
+    pub fn preset(mut self, preset: Preset) -> CrtSettingsBuilder {
+        self.look = self.look.or(preset.look());
+        return self;
+    }
+
+    pub fn upsampling(mut self, upsampling: u32) -> CrtSettingsBuilder {
+        self.look.upsampling = Some(upsampling);
+        return self;
+    }
+
+    pub fn pixel(mut self, pixel: u32) -> CrtSettingsBuilder {
+        self.look.pixel = Some(pixel);
+        return self;
+    }
+
+    pub fn scanlines(mut self, scanlines: Scanlines) -> CrtSettingsBuilder {
+        self.look.scanlines = Some(scanlines);
+        return self;
+    }
+
+    pub fn native_height(mut self, native_height: u32) -> CrtSettingsBuilder {
+        self.look.native_height = Some(native_height);
+        return self;
+    }
+
+    pub fn downscale_native(mut self, downscale_native: bool) -> CrtSettingsBuilder {
+        self.look.downscale_native = Some(downscale_native);
+        return self;
+    }
+
+    pub fn brightness(mut self, brightness: i32) -> CrtSettingsBuilder {
+        self.look.brightness = Some(brightness);
+        return self;
+    }
+
+    pub fn contrast(mut self, contrast: f32) -> CrtSettingsBuilder {
+        self.look.contrast = Some(contrast);
+        return self;
+    }
+
+    pub fn mask(mut self, mask: MaskType) -> CrtSettingsBuilder {
+        self.look.mask = Some(mask);
+        return self;
+    }
+
+    pub fn mask_tile(mut self, mask_tile: impl Into<String>) -> CrtSettingsBuilder {
+        self.look.mask_tile = Some(mask_tile.into());
+        return self;
+    }
+
+    pub fn red(mut self, red: Rgb<u8>) -> CrtSettingsBuilder {
+        self.look.red = Some(red);
+        return self;
+    }
+
+    pub fn green(mut self, green: Rgb<u8>) -> CrtSettingsBuilder {
+        self.look.green = Some(green);
+        return self;
+    }
+
+    pub fn blue(mut self, blue: Rgb<u8>) -> CrtSettingsBuilder {
+        self.look.blue = Some(blue);
+        return self;
+    }
+
+    pub fn amplification(mut self, amplification: f32) -> CrtSettingsBuilder {
+        self.look.amplification = Some(amplification);
+        return self;
+    }
+
+    pub fn blur(mut self, blur: f32) -> CrtSettingsBuilder {
+        self.look.blur = Some(blur);
+        return self;
+    }
+
+    pub fn curvature_x(mut self, curvature_x: f32) -> CrtSettingsBuilder {
+        self.look.curvature_x = Some(curvature_x);
+        return self;
+    }
+
+    pub fn curvature_y(mut self, curvature_y: f32) -> CrtSettingsBuilder {
+        self.look.curvature_y = Some(curvature_y);
+        return self;
+    }
+
+    pub fn border(mut self, border: Rgb<u8>) -> CrtSettingsBuilder {
+        self.look.border = Some(border);
+        return self;
+    }
+
+    pub fn vignette(mut self, vignette: f32) -> CrtSettingsBuilder {
+        self.look.vignette = Some(vignette);
+        return self;
+    }
+
+    pub fn vignette_falloff(mut self, vignette_falloff: f32) -> CrtSettingsBuilder {
+        self.look.vignette_falloff = Some(vignette_falloff);
+        return self;
+    }
+
+    pub fn corner_radius(mut self, corner_radius: f32) -> CrtSettingsBuilder {
+        self.look.corner_radius = Some(corner_radius);
+        return self;
+    }
+
+    pub fn bloom(mut self, bloom: f32) -> CrtSettingsBuilder {
+        self.look.bloom = Some(bloom);
+        return self;
+    }
+
+    pub fn bloom_threshold(mut self, bloom_threshold: f32) -> CrtSettingsBuilder {
+        self.look.bloom_threshold = Some(bloom_threshold);
+        return self;
+    }
+
+    pub fn bloom_radius(mut self, bloom_radius: f32) -> CrtSettingsBuilder {
+        self.look.bloom_radius = Some(bloom_radius);
+        return self;
+    }
+
+    pub fn par(mut self, par: f32) -> CrtSettingsBuilder {
+        self.look.par = Some(par);
+        return self;
+    }
+
+    pub fn display_aspect(mut self, display_aspect: f32) -> CrtSettingsBuilder {
+        self.look.display_aspect = Some(display_aspect);
+        return self;
+    }
+
+    pub fn output_scale(mut self, output_scale: f32) -> CrtSettingsBuilder {
+        self.look.output_scale = Some(output_scale);
+        return self;
+    }
+
+    pub fn output_size(mut self, output_size: (u32, u32)) -> CrtSettingsBuilder {
+        self.look.output_size = Some(output_size);
+        return self;
+    }
+
+    pub fn noise(mut self, noise: f32) -> CrtSettingsBuilder {
+        self.look.noise = Some(noise);
+        return self;
+    }
+
+    pub fn stages(mut self, stages: Vec<StageKind>) -> CrtSettingsBuilder {
+        self.look.stages = Some(stages);
+        return self;
+    }
+
+    pub fn alpha(mut self, alpha: AlphaMode) -> CrtSettingsBuilder {
+        self.look.alpha = Some(alpha);
+        return self;
+    }
+
+    pub fn background(mut self, background: Rgb<u8>) -> CrtSettingsBuilder {
+        self.look.background = Some(background);
+        return self;
+    }
+
+    pub fn gamma(mut self, gamma: f32) -> CrtSettingsBuilder {
+        self.look.gamma = Some(gamma);
+        return self;
+    }
+
+    pub fn scanline_mode(mut self, scanline_mode: ScanlineMode) -> CrtSettingsBuilder {
+        self.look.scanline_mode = Some(scanline_mode);
+        return self;
+    }
+
+    pub fn beam_min(mut self, beam_min: f32) -> CrtSettingsBuilder {
+        self.look.beam_min = Some(beam_min);
+        return self;
+    }
+
+    pub fn beam_max(mut self, beam_max: f32) -> CrtSettingsBuilder {
+        self.look.beam_max = Some(beam_max);
+        return self;
+    }
+
+    pub fn beam_profile(mut self, beam_profile: BeamProfile) -> CrtSettingsBuilder {
+        self.look.beam_profile = Some(beam_profile);
+        return self;
+    }
+
+    pub fn build(self) -> Result<CrtSettings, SettingsError> {
+        return self
+            .look
+            .or(Look {
+                scanlines: Some(Scanlines::Auto),
+                brightness: Some(0),
+                contrast: Some(0.0),
+                ..Look::default()
+            })
+            .resolve();
+    }
+}
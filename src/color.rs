@@ -0,0 +1,181 @@
+use crate::parallel::par_for_each_pixel;
+use image::{ColorType, DynamicImage, ImageBuffer, Luma, Rgb};
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+pub fn parse_color(value: &str) -> Result<Rgb<u8>, String> {
+    let value = value.trim();
+
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 || !hex.chars().all(|digit| digit.is_ascii_hexdigit()) {
+            return Err(format!("`{}` is not a #rrggbb hex color", value));
+        }
+
+        let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).unwrap();
+        return Ok(Rgb([channel(0), channel(2), channel(4)]));
+    }
+
+    let channels = value
+        .split(',')
+        .map(|channel| channel.trim().parse::<u8>())
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| format!("`{}` is not an r,g,b triplet of values 0-255", value))?;
+
+    match channels[..] {
+        [red, green, blue] => return Ok(Rgb([red, green, blue])),
+        _ => return Err(format!("`{}` must have exactly 3 channels", value)),
+    }
+}
+
+pub fn serialize<S: Serializer>(color: &Rgb<u8>, serializer: S) -> Result<S::Ok, S::Error> {
+    return serializer.serialize_str(&format!(
+        "#{:02x}{:02x}{:02x}",
+        color[0], color[1], color[2]
+    ));
+}
+
+pub fn deserialize_some<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Rgb<u8>>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    return parse_color(&value).map(Some).map_err(de::Error::custom);
+}
+
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        return value / 12.92;
+    } else {
+        return ((value + 0.055) / 1.055).powf(2.4);
+    }
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        return value * 12.92;
+    } else {
+        return 1.055 * value.powf(1.0 / 2.4) - 0.055;
+    }
+}
+
+pub fn linear_color(color: Rgb<u8>) -> Rgb<f32> {
+    return Rgb(color
+        .0
+        .map(|channel| srgb_to_linear(channel as f32 / 255.0)));
+}
+
+pub fn linearize(image: DynamicImage, gamma: Option<f32>) -> ImageBuffer<Rgb<f32>, Vec<f32>> {
+    // Floating point formats such as OpenEXR already store linear light.
+    let is_linear = matches!(image.color(), ColorType::Rgb32F | ColorType::Rgba32F);
+    let mut linear_image = image.into_rgb32f();
+
+    if is_linear && gamma.is_none() {
+        return linear_image;
+    }
+
+    par_for_each_pixel(&mut linear_image, |_, _, pixel| {
+        *pixel = Rgb(pixel.0.map(|channel| match gamma {
+            Some(gamma) => channel.max(0.0).powf(gamma),
+            None => srgb_to_linear(channel),
+        }));
+    });
+
+    return linear_image;
+}
+
+/// Turns a knob of a real set, which acts on the gamma encoded signal rather than on light.
+/// Premultiplied color is divided by its alpha around the change, and light above `white` clips.
+pub fn adjust_encoded<F: Fn(f32) -> f32 + Sync>(
+    image: &mut ImageBuffer<Rgb<f32>, Vec<f32>>,
+    alpha: Option<&ImageBuffer<Luma<f32>, Vec<f32>>>,
+    white: f32,
+    adjust: F,
+) {
+    par_for_each_pixel(image, |x, y, pixel| {
+        let coverage = match alpha {
+            Some(alpha) => alpha.get_pixel(x, y)[0].min(1.0),
+            None => 1.0,
+        };
+
+        if coverage <= 0.0 {
+            return;
+        }
+
+        *pixel = Rgb(pixel.0.map(|channel| {
+            let encoded = linear_to_srgb((channel / coverage).clamp(0.0, white));
+
+            srgb_to_linear(adjust(encoded).clamp(0.0, white)) * coverage
+        }));
+    });
+}
+
+/// Gamma encodes linear light for integer output. Float output stays linear and is not clipped
+/// at white.
+pub fn encode_srgb(
+    image: &ImageBuffer<Rgb<f32>, Vec<f32>>,
+    linear_output: bool,
+) -> ImageBuffer<Rgb<f32>, Vec<f32>> {
+    let mut encoded_image = image.clone();
+
+    par_for_each_pixel(&mut encoded_image, |_, _, pixel| {
+        *pixel = Rgb(pixel.0.map(|channel| match linear_output {
+            true => channel.max(0.0),
+            false => linear_to_srgb(channel.clamp(0.0, 1.0)),
+        }));
+    });
+
+    return encoded_image;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::RgbImage;
+
+    #[test]
+    fn parses_hex_and_triplets() {
+        assert_eq!(parse_color("#ff8000"), Ok(Rgb([255, 128, 0])));
+        assert_eq!(parse_color(" #FF8000 "), Ok(Rgb([255, 128, 0])));
+        assert_eq!(parse_color("255, 128,0"), Ok(Rgb([255, 128, 0])));
+    }
+
+    #[test]
+    fn rejects_malformed_colors() {
+        assert!(parse_color("#ff80").is_err());
+        assert!(parse_color("#gg8000").is_err());
+        assert!(parse_color("255,128").is_err());
+        assert!(parse_color("256,0,0").is_err());
+        assert!(parse_color("").is_err());
+    }
+
+    #[test]
+    fn srgb_round_trips() {
+        for value in [0.0, 0.002, 0.04, 0.5, 1.0] {
+            assert!((linear_to_srgb(srgb_to_linear(value)) - value).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn mid_grey_is_darker_in_linear_light() {
+        let grey = DynamicImage::ImageRgb8(RgbImage::from_pixel(2, 2, Rgb([128, 128, 128])));
+        let linear = linearize(grey.clone(), None);
+        let emulated = linearize(grey, Some(2.5));
+
+        assert!((linear.get_pixel(0, 0)[0] - 0.2158).abs() < 1e-3);
+        assert!((emulated.get_pixel(0, 0)[0] - 0.1785).abs() < 1e-3);
+
+        let encoded = encode_srgb(&linear, false);
+        assert!((encoded.get_pixel(1, 1)[0] - 128.0 / 255.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn knobs_act_on_the_encoded_signal_of_unpremultiplied_color() {
+        let grey = srgb_to_linear(128.0 / 255.0);
+        let mut image = ImageBuffer::from_pixel(2, 1, Rgb([grey * 0.5; 3]));
+        let alpha = ImageBuffer::from_fn(2, 1, |x, _| Luma([[0.5, 0.0][x as usize]]));
+
+        adjust_encoded(&mut image, Some(&alpha), 1.0, |value| value + 0.1);
+
+        let brightened = linear_to_srgb(image.get_pixel(0, 0)[0] / 0.5);
+        assert!((brightened - (128.0 / 255.0 + 0.1)).abs() < 1e-5);
+        assert_eq!(image.get_pixel(1, 0), &Rgb([grey * 0.5; 3]));
+    }
+}
@@ -0,0 +1,138 @@
+use image::{ImageBuffer, Pixel};
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        return a;
+    } else {
+        return gcd(b, a % b);
+    }
+}
+
+/// Common divisor of all run lengths, or 0 for a single run, which says nothing about scaling.
+fn run_length_gcd(repeats: impl Iterator<Item = bool>) -> u32 {
+    let mut scale = 0;
+    let mut run = 1;
+
+    for repeated in repeats {
+        if repeated {
+            run += 1;
+        } else {
+            scale = gcd(scale, run);
+            run = 1;
+        }
+    }
+
+    if scale == 0 {
+        return 0;
+    }
+
+    return gcd(scale, run);
+}
+
+fn column_run_gcd<P: Pixel>(image: &ImageBuffer<P, Vec<P::Subpixel>>) -> u32 {
+    let (res_x, res_y) = image.dimensions();
+    let same =
+        |x: u32, y: u32| image.get_pixel(x, y).channels() == image.get_pixel(x - 1, y).channels();
+
+    return run_length_gcd((1..res_x).map(|x| (0..res_y).all(|y| same(x, y))));
+}
+
+fn row_run_gcd<P: Pixel>(image: &ImageBuffer<P, Vec<P::Subpixel>>) -> u32 {
+    let (res_x, res_y) = image.dimensions();
+    let row_length = res_x as usize * P::CHANNEL_COUNT as usize;
+    let row = |y: u32| &image.as_raw()[y as usize * row_length..(y as usize + 1) * row_length];
+
+    return run_length_gcd((1..res_y).map(|y| row(y) == row(y - 1)));
+}
+
+pub fn detect_column_scale<P: Pixel>(image: &ImageBuffer<P, Vec<P::Subpixel>>) -> u32 {
+    return column_run_gcd(image).max(1);
+}
+
+pub fn detect_row_scale<P: Pixel>(image: &ImageBuffer<P, Vec<P::Subpixel>>) -> u32 {
+    return row_run_gcd(image).max(1);
+}
+
+pub fn detect_scale<P: Pixel>(image: &ImageBuffer<P, Vec<P::Subpixel>>) -> (u32, u32) {
+    return (detect_column_scale(image), detect_row_scale(image));
+}
+
+/// Detects the scale shared by every frame of an animation. Uniform frames are skipped.
+pub fn detect_common_scale<'a, P: Pixel + 'a>(
+    images: impl Iterator<Item = &'a ImageBuffer<P, Vec<P::Subpixel>>>,
+) -> (u32, u32) {
+    let (columns, rows) = images.fold((0, 0), |(columns, rows), image| {
+        (
+            gcd(columns, column_run_gcd(image)),
+            gcd(rows, row_run_gcd(image)),
+        )
+    });
+
+    return (columns.max(1), rows.max(1));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::imageops::{resize, FilterType};
+    use image::Rgb;
+
+    fn noise(res_x: u32, res_y: u32) -> ImageBuffer<Rgb<f32>, Vec<f32>> {
+        return ImageBuffer::from_fn(res_x, res_y, |x, y| {
+            let value = ((x * 7 + y * 13) % 5) as f32 / 4.0;
+            Rgb([value, 1.0 - value, (x % 2) as f32])
+        });
+    }
+
+    #[test]
+    fn run_lengths_give_their_common_divisor() {
+        let runs = |lengths: &[usize]| {
+            lengths
+                .iter()
+                .flat_map(|&length| (0..length).map(move |index| index != 0))
+                .skip(1)
+                .collect::<Vec<bool>>()
+        };
+
+        assert_eq!(run_length_gcd(runs(&[4, 8, 4, 12]).into_iter()), 4);
+        assert_eq!(run_length_gcd(runs(&[3, 6, 2]).into_iter()), 1);
+        assert_eq!(run_length_gcd(runs(&[1, 1, 1]).into_iter()), 1);
+        assert_eq!(run_length_gcd(runs(&[7]).into_iter()), 0);
+    }
+
+    #[test]
+    fn nearest_upscale_is_detected() {
+        let native = noise(16, 12);
+
+        assert_eq!(detect_scale(&native), (1, 1));
+        assert_eq!(
+            detect_scale(&resize(&native, 64, 48, FilterType::Nearest)),
+            (4, 4)
+        );
+        assert_eq!(
+            detect_scale(&resize(&native, 32, 36, FilterType::Nearest)),
+            (2, 3)
+        );
+    }
+
+    #[test]
+    fn uniform_image_has_no_scale() {
+        let uniform = ImageBuffer::from_pixel(20, 10, Rgb([0.5, 0.5, 0.5]));
+
+        assert_eq!(detect_scale(&uniform), (1, 1));
+    }
+
+    #[test]
+    fn animation_scale_is_shared_by_all_frames() {
+        let uniform = ImageBuffer::from_pixel(48, 48, Rgb([0.0, 0.0, 0.0]));
+        let coarse = resize(&noise(6, 6), 48, 48, FilterType::Nearest);
+        let fine = resize(&noise(12, 24), 48, 48, FilterType::Nearest);
+
+        assert_eq!(detect_common_scale([&uniform, &coarse].into_iter()), (8, 8));
+        assert_eq!(
+            detect_common_scale([&uniform, &coarse, &fine].into_iter()),
+            (4, 2)
+        );
+        assert_eq!(detect_common_scale([&uniform].into_iter()), (1, 1));
+    }
+}
@@ -0,0 +1,91 @@
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+pub fn parse_size(value: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("`{}` is not a WIDTHxHEIGHT size", value);
+    let (width, height) = value.trim().split_once(['x', 'X']).ok_or_else(invalid)?;
+    let width = width.trim().parse::<u32>().map_err(|_| invalid())?;
+    let height = height.trim().parse::<u32>().map_err(|_| invalid())?;
+
+    if width == 0 || height == 0 {
+        return Err(format!("`{}` must not have a zero dimension", value));
+    }
+
+    return Ok((width, height));
+}
+
+pub fn serialize_size<S: Serializer>(
+    size: &Option<(u32, u32)>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match size {
+        Some((width, height)) => return serializer.serialize_str(&format!("{}x{}", width, height)),
+        None => return serializer.serialize_none(),
+    }
+}
+
+pub fn deserialize_size<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<(u32, u32)>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    return parse_size(&value).map(Some).map_err(de::Error::custom);
+}
+
+pub fn parse_ratio(value: &str) -> Result<f32, String> {
+    let invalid = || format!("`{}` is not a ratio like 8:7 or 1.14", value);
+    let ratio = match value.trim().split_once(':') {
+        Some((numerator, denominator)) => {
+            let numerator = numerator.trim().parse::<f32>().map_err(|_| invalid())?;
+            let denominator = denominator.trim().parse::<f32>().map_err(|_| invalid())?;
+            numerator / denominator
+        }
+        None => value.trim().parse::<f32>().map_err(|_| invalid())?,
+    };
+
+    if !ratio.is_finite() || ratio <= 0.0 {
+        return Err(format!("`{}` must be a positive ratio", value));
+    }
+
+    return Ok(ratio);
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RatioValue {
+    Number(f32),
+    Text(String),
+}
+
+pub fn deserialize_ratio<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<f32>, D::Error> {
+    let ratio = match RatioValue::deserialize(deserializer)? {
+        RatioValue::Number(ratio) => parse_ratio(&ratio.to_string()),
+        RatioValue::Text(ratio) => parse_ratio(&ratio),
+    };
+
+    return ratio.map(Some).map_err(de::Error::custom);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(parse_size("1920x1080"), Ok((1920, 1080)));
+        assert_eq!(parse_size(" 640 X 480 "), Ok((640, 480)));
+        assert!(parse_size("0x480").is_err());
+        assert!(parse_size("640").is_err());
+        assert!(parse_size("640x-1").is_err());
+    }
+
+    #[test]
+    fn parses_ratios() {
+        assert_eq!(parse_ratio("8:7"), Ok(8.0 / 7.0));
+        assert_eq!(parse_ratio("1.5"), Ok(1.5));
+        assert!(parse_ratio("4:0").is_err());
+        assert!(parse_ratio("-1").is_err());
+        assert!(parse_ratio("NaN").is_err());
+        assert!(parse_ratio("wide").is_err());
+    }
+}
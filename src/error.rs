@@ -0,0 +1,72 @@
+use crate::settings::SettingsError;
+use image::ImageError;
+use std::{error::Error, fmt, io};
+
+#[derive(Debug)]
+pub enum CrtError {
+    Io(io::Error),
+    Decode(ImageError),
+    Encode(ImageError),
+    Config(String),
+    InvalidPath(String),
+    InvalidParameter(SettingsError),
+}
+
+impl CrtError {
+    /// Wraps an error from decoding, keeping plain I/O failures such as a missing file apart.
+    pub fn decode(error: ImageError) -> CrtError {
+        match error {
+            ImageError::IoError(error) => return CrtError::Io(error),
+            error => return CrtError::Decode(error),
+        }
+    }
+
+    pub fn encode(error: ImageError) -> CrtError {
+        match error {
+            ImageError::IoError(error) => return CrtError::Io(error),
+            error => return CrtError::Encode(error),
+        }
+    }
+}
+
+impl fmt::Display for CrtError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CrtError::Io(error) => return write!(formatter, "{}", error),
+            CrtError::Decode(error) => {
+                return write!(formatter, "failed to decode image: {}", error)
+            }
+            CrtError::Encode(error) => {
+                return write!(formatter, "failed to encode image: {}", error)
+            }
+            CrtError::Config(message) => return write!(formatter, "invalid config: {}", message),
+            CrtError::InvalidPath(message) => return write!(formatter, "{}", message),
+            CrtError::InvalidParameter(error) => {
+                return write!(formatter, "invalid parameter: {}", error)
+            }
+        }
+    }
+}
+
+impl Error for CrtError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CrtError::Io(error) => return Some(error),
+            CrtError::Decode(error) | CrtError::Encode(error) => return Some(error),
+            CrtError::InvalidParameter(error) => return Some(error),
+            CrtError::Config(_) | CrtError::InvalidPath(_) => return None,
+        }
+    }
+}
+
+impl From<io::Error> for CrtError {
+    fn from(error: io::Error) -> CrtError {
+        return CrtError::Io(error);
+    }
+}
+
+impl From<SettingsError> for CrtError {
+    fn from(error: SettingsError) -> CrtError {
+        return CrtError::InvalidParameter(error);
+    }
+}
@@ -0,0 +1,314 @@
+use crate::{
+    alpha::{attach_alpha, composite, extract_alpha, premultiply, unpremultiply, AlphaMode},
+    color::{encode_srgb, linear_color, linearize},
+    detect::detect_common_scale,
+    error::CrtError,
+    output::BitDepth,
+    pipeline::{Frame, Pipeline},
+    scanlines::Scanlines,
+    settings::CrtSettings,
+};
+use image::{
+    imageops::{resize, FilterType},
+    DynamicImage, ImageBuffer, Luma, Pixel, Rgb,
+};
+use std::iter;
+
+/// The input in linear light, composited or premultiplied depending on the alpha mode.
+struct LinearInput {
+    image: ImageBuffer<Rgb<f32>, Vec<f32>>,
+    alpha: Option<ImageBuffer<Luma<f32>, Vec<f32>>>,
+}
+
+/// Applies the CRT effect to in-memory images with a fixed set of settings.
+pub struct CrtFilter {
+    settings: CrtSettings,
+    pipeline: Pipeline,
+}
+
+impl CrtFilter {
+    /// Creates a filter running the stages listed in the settings, loading the mask tile if needed.
+    pub fn new(settings: CrtSettings) -> Result<CrtFilter, CrtError> {
+        settings.validate()?;
+        let pipeline = Pipeline::from_settings(&settings)?;
+
+        return Ok(CrtFilter { settings, pipeline });
+    }
+
+    /// Creates a filter running a custom pipeline instead of the stages listed in the settings.
+    pub fn with_pipeline(settings: CrtSettings, pipeline: Pipeline) -> Result<CrtFilter, CrtError> {
+        settings.validate()?;
+
+        return Ok(CrtFilter { settings, pipeline });
+    }
+
+    pub fn settings(&self) -> &CrtSettings {
+        return &self.settings;
+    }
+
+    /// Filters an image, returning it at the bit depth of the input.
+    pub fn process(&self, image: impl Into<DynamicImage>) -> DynamicImage {
+        let image = image.into();
+        let bit_depth = BitDepth::of(image.color());
+
+        return self.process_to_depth(image, bit_depth);
+    }
+
+    /// Filters every frame of an animation, keeping the frame delays. Frames stay 8-bit RGBA.
+    pub fn process_frames(&self, frames: Vec<image::Frame>) -> Vec<image::Frame> {
+        // Detecting once keeps the native size and scanlines from changing between frames.
+        let native_scale = self.native_scale(frames.iter().map(|frame| frame.buffer()));
+
+        return frames
+            .into_iter()
+            .map(|frame| {
+                let delay = frame.delay();
+                let input = self.linear_input(DynamicImage::ImageRgba8(frame.into_buffer()));
+                let image = self.render(input, native_scale, BitDepth::Eight);
+
+                image::Frame::from_parts(image.into_rgba8(), 0, 0, delay)
+            })
+            .collect();
+    }
+
+    /// Filters an image, returning it at the given bit depth. Float output is linear light.
+    pub fn process_to_depth(
+        &self,
+        image: impl Into<DynamicImage>,
+        bit_depth: BitDepth,
+    ) -> DynamicImage {
+        let input = self.linear_input(image.into());
+        let native_scale = self.native_scale(iter::once(&input.image));
+
+        return self.render(input, native_scale, bit_depth);
+    }
+
+    fn linear_input(&self, image: DynamicImage) -> LinearInput {
+        let settings = &self.settings;
+        let mut alpha = match settings.alpha {
+            AlphaMode::Discard => None,
+            AlphaMode::Keep | AlphaMode::Composite => Some(extract_alpha(&image)),
+        };
+        let mut image = linearize(image, settings.gamma);
+
+        match (settings.alpha, &alpha) {
+            (AlphaMode::Composite, Some(coverage)) => {
+                composite(&mut image, coverage, linear_color(settings.background));
+                alpha = None;
+            }
+            // The stages run on premultiplied color until the alpha is attached again.
+            (AlphaMode::Keep, Some(coverage)) => premultiply(&mut image, coverage),
+            _ => {}
+        }
+
+        return LinearInput { image, alpha };
+    }
+
+    /// Detects integer upscaling of the input, if the settings need it.
+    fn native_scale<'a, P: Pixel + 'a>(
+        &self,
+        images: impl Iterator<Item = &'a ImageBuffer<P, Vec<P::Subpixel>>>,
+    ) -> (u32, u32) {
+        let settings = &self.settings;
+        let auto_scanlines =
+            settings.scanlines == Scanlines::Auto && settings.native_height.is_none();
+
+        if !settings.downscale_native && !auto_scanlines {
+            return (1, 1);
+        }
+
+        return detect_common_scale(images);
+    }
+
+    fn render(
+        &self,
+        input: LinearInput,
+        (scale_x, scale_y): (u32, u32),
+        bit_depth: BitDepth,
+    ) -> DynamicImage {
+        let settings = &self.settings;
+        let upsampling = settings.upsampling;
+        let LinearInput {
+            mut image,
+            mut alpha,
+        } = input;
+        let (input_x, input_y) = image.dimensions();
+
+        if settings.downscale_native {
+            let (native_x, native_y) = (input_x / scale_x, input_y / scale_y);
+
+            image = resize(&image, native_x, native_y, FilterType::Nearest);
+            alpha = alpha.map(|alpha| resize(&alpha, native_x, native_y, FilterType::Nearest));
+        }
+
+        let (res_x, res_y) = image.dimensions();
+        let pixel_aspect = match settings.display_aspect {
+            Some(display_aspect) => display_aspect * res_y as f32 / res_x as f32,
+            None => settings.par,
+        };
+        // The width follows the shape of the native picture, which may have been scaled unevenly.
+        let display_ratio = res_x as f32 * pixel_aspect / res_y as f32;
+        let (output_x, output_y) = settings.output_size.unwrap_or((
+            ((input_y as f32 * display_ratio * settings.output_scale).round() as u32).max(1),
+            ((input_y as f32 * settings.output_scale).round() as u32).max(1),
+        ));
+        let scanline_count = match settings.scanlines {
+            Scanlines::Count(number) => number,
+            Scanlines::Auto => settings.native_height.unwrap_or(input_y / scale_y) as usize,
+        };
+
+        let (upsampled_x, upsampled_y) = (
+            (((res_x * upsampling) as f32 * pixel_aspect).round() as u32).max(1),
+            res_y * upsampling,
+        );
+        let mut frame = Frame {
+            source: image.clone(),
+            image,
+            alpha,
+            coverage: ImageBuffer::from_pixel(res_x, res_y, Luma([1.0])),
+            scanline_count,
+            upsampled_size: (upsampled_x, upsampled_y),
+            output_size: (output_x, output_y),
+            white: if bit_depth == BitDepth::Float {
+                f32::INFINITY
+            } else {
+                1.0
+            },
+        };
+
+        self.pipeline.run(&mut frame, settings);
+
+        if let Some(alpha) = &frame.alpha {
+            unpremultiply(&mut frame.image, alpha);
+        }
+
+        let processed_image = encode_srgb(&frame.image, bit_depth == BitDepth::Float);
+
+        let output_image = match frame.alpha {
+            Some(alpha) => DynamicImage::ImageRgba32F(attach_alpha(&processed_image, &alpha)),
+            None => DynamicImage::ImageRgb32F(processed_image),
+        };
+        return bit_depth.convert(output_image);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::{Stage, StageKind};
+    use image::{Delay, RgbImage, Rgba, RgbaImage};
+    use std::sync::Mutex;
+
+    #[test]
+    fn display_aspect_holds_for_uneven_upscales() {
+        // 16x12 native pixels, doubled horizontally and tripled vertically.
+        let native = RgbImage::from_fn(16, 12, |x, y| Rgb([(x * 16) as u8, (y * 20) as u8, 0]));
+        let input = resize(&native, 32, 36, FilterType::Nearest);
+        let settings = CrtSettings::builder()
+            .pixel(3)
+            .upsampling(3)
+            .downscale_native(true)
+            .display_aspect(4.0 / 3.0)
+            .build()
+            .unwrap();
+        let output = CrtFilter::new(settings).unwrap().process(input);
+
+        assert_eq!((output.width(), output.height()), (48, 36));
+    }
+
+    #[test]
+    fn kept_alpha_does_not_bleed_transparent_color() {
+        // Opaque white on the left, fully transparent red on the right.
+        let input = RgbaImage::from_fn(16, 8, |x, _| match x < 8 {
+            true => Rgba([255, 255, 255, 255]),
+            false => Rgba([255, 0, 0, 0]),
+        });
+        let settings = CrtSettings::builder()
+            .pixel(3)
+            .alpha(AlphaMode::Keep)
+            .blur(1.5)
+            .stages(vec![
+                StageKind::Upsample,
+                StageKind::Blur,
+                StageKind::Resize,
+            ])
+            .build()
+            .unwrap();
+        let output = CrtFilter::new(settings)
+            .unwrap()
+            .process(input)
+            .into_rgba8();
+
+        for pixel in output.pixels().filter(|pixel| pixel[3] > 0) {
+            assert!(pixel[0].abs_diff(pixel[1]) <= 1, "{:?}", pixel);
+        }
+        assert!(output.pixels().any(|pixel| pixel[3] > 0 && pixel[3] < 255));
+    }
+
+    #[test]
+    fn bloom_after_curvature_keeps_the_border_dark() {
+        // A white block in the top right corner, which curvature pulls away from the edge.
+        let input = RgbImage::from_fn(64, 48, |x, y| match x >= 48 && y < 12 {
+            true => Rgb([255, 255, 255]),
+            false => Rgb([0, 0, 0]),
+        });
+        let settings = CrtSettings::builder()
+            .pixel(3)
+            .curvature_x(0.5)
+            .curvature_y(0.5)
+            .bloom(2.0)
+            .bloom_threshold(0.1)
+            .stages(vec![
+                StageKind::Upsample,
+                StageKind::Curvature,
+                StageKind::Bloom,
+                StageKind::Resize,
+            ])
+            .build()
+            .unwrap();
+        let output = CrtFilter::new(settings).unwrap().process(input).into_rgb8();
+        let (res_x, res_y) = output.dimensions();
+
+        assert!(output.get_pixel(res_x - 1, 0)[0] < 16);
+        assert!(output.get_pixel(0, res_y - 1)[0] < 16);
+    }
+
+    static RECORDED_SCANLINES: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+    struct RecordScanlines;
+
+    impl Stage for RecordScanlines {
+        fn apply(&self, frame: &mut Frame, _: &CrtSettings) {
+            RECORDED_SCANLINES
+                .lock()
+                .unwrap()
+                .push(frame.scanline_count);
+        }
+    }
+
+    #[test]
+    fn animation_frames_share_detected_scanlines() {
+        let blank = RgbaImage::from_pixel(32, 24, Rgba([0, 0, 0, 255]));
+        let art = RgbaImage::from_fn(32, 24, |x, y| match (x / 4 + y / 4) % 2 {
+            0 => Rgba([255, 255, 255, 255]),
+            _ => Rgba([0, 0, 0, 255]),
+        });
+        let frames = [blank, art]
+            .into_iter()
+            .map(|image| image::Frame::from_parts(image, 0, 0, Delay::from_numer_denom_ms(100, 1)))
+            .collect();
+        let settings = CrtSettings::builder()
+            .pixel(3)
+            .scanlines(Scanlines::Auto)
+            .build()
+            .unwrap();
+        let mut pipeline = Pipeline::new();
+        pipeline.push(Box::new(RecordScanlines));
+
+        CrtFilter::with_pipeline(settings, pipeline)
+            .unwrap()
+            .process_frames(frames);
+
+        assert_eq!(*RECORDED_SCANLINES.lock().unwrap(), [6, 6]);
+    }
+}
@@ -0,0 +1,62 @@
+use crate::parallel::par_for_each_pixel;
+use image::{ImageBuffer, Pixel};
+
+fn sample_bilinear<P: Pixel<Subpixel = f32>>(
+    image: &ImageBuffer<P, Vec<f32>>,
+    x: f64,
+    y: f64,
+) -> P {
+    let (res_x, res_y) = image.dimensions();
+    let x = x.clamp(0.0, (res_x - 1) as f64);
+    let y = y.clamp(0.0, (res_y - 1) as f64);
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(res_x - 1), (y0 + 1).min(res_y - 1));
+    let (fx, fy) = (x - x0 as f64, y - y0 as f64);
+
+    let corners = [
+        (image.get_pixel(x0, y0), (1.0 - fx) * (1.0 - fy)),
+        (image.get_pixel(x1, y0), fx * (1.0 - fy)),
+        (image.get_pixel(x0, y1), (1.0 - fx) * fy),
+        (image.get_pixel(x1, y1), fx * fy),
+    ];
+    let mut sample = *corners[0].0;
+
+    for (index, channel) in sample.channels_mut().iter_mut().enumerate() {
+        *channel = corners
+            .iter()
+            .map(|(pixel, weight)| pixel.channels()[index] as f64 * weight)
+            .sum::<f64>() as f32;
+    }
+
+    return sample;
+}
+
+pub fn apply_curvature<P: Pixel<Subpixel = f32> + Sync>(
+    image: &ImageBuffer<P, Vec<f32>>,
+    curvature_x: f32,
+    curvature_y: f32,
+    border: P,
+) -> ImageBuffer<P, Vec<f32>> {
+    let (res_x, res_y) = image.dimensions();
+    let (half_x, half_y) = (res_x as f64 / 2.0, res_y as f64 / 2.0);
+    let mut curved_image = ImageBuffer::from_pixel(res_x, res_y, border);
+
+    par_for_each_pixel(&mut curved_image, |x, y, pixel| {
+        let u = (x as f64 + 0.5) / half_x - 1.0;
+        let v = (y as f64 + 0.5) / half_y - 1.0;
+        let source_u = u * (1.0 + curvature_x as f64 * v * v);
+        let source_v = v * (1.0 + curvature_y as f64 * u * u);
+
+        *pixel = if source_u.abs() > 1.0 || source_v.abs() > 1.0 {
+            border
+        } else {
+            sample_bilinear(
+                image,
+                (source_u + 1.0) * half_x - 0.5,
+                (source_v + 1.0) * half_y - 0.5,
+            )
+        };
+    });
+
+    return curved_image;
+}
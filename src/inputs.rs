@@ -0,0 +1,171 @@
+use crt::{animation::decode_animation, CrtError};
+use glob::glob;
+use image::{DynamicImage, Frame, ImageFormat};
+use std::{
+    error::Error,
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+fn is_glob(pattern: &str) -> bool {
+    return pattern.contains(['*', '?', '[']);
+}
+
+fn is_image(path: &Path) -> bool {
+    return path.is_file() && ImageFormat::from_path(path).is_ok();
+}
+
+fn scan_directory(
+    directory: &Path,
+    recursive: bool,
+    inputs: &mut Vec<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    let mut entries = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<PathBuf>, _>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            if recursive {
+                scan_directory(&path, recursive, inputs)?;
+            }
+        } else if is_image(&path) {
+            inputs.push(path);
+        }
+    }
+
+    return Ok(());
+}
+
+pub fn collect_inputs(paths: &[PathBuf], recursive: bool) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut inputs = Vec::new();
+
+    for path in paths {
+        // Names that aren't valid UTF-8 can't be glob patterns, but are still plain files.
+        let pattern = path.to_str().filter(|pattern| is_glob(pattern));
+
+        if path.is_dir() {
+            scan_directory(path, recursive, &mut inputs).map_err(|error| {
+                format!("failed to read directory {}: {}", path.display(), error)
+            })?;
+        } else if let (Some(pattern), false) = (pattern, path.exists()) {
+            let matches = glob(pattern)?
+                .collect::<Result<Vec<PathBuf>, _>>()?
+                .into_iter()
+                .filter(|path| is_image(path))
+                .collect::<Vec<PathBuf>>();
+
+            if matches.is_empty() {
+                eprintln!("{}: no images match this pattern", pattern);
+            }
+
+            inputs.extend(matches);
+        } else {
+            inputs.push(path.clone());
+        }
+    }
+
+    return Ok(inputs);
+}
+
+pub enum Input {
+    Still(DynamicImage),
+    Animated(Vec<Frame>),
+}
+
+/// Loads an image, keeping all of its frames if it is animated.
+pub fn load_image(path: &Path) -> Result<Input, CrtError> {
+    let mut buffer = Vec::new();
+
+    if path == Path::new("-") {
+        io::stdin().lock().read_to_end(&mut buffer)?;
+    } else {
+        buffer = fs::read(path)?;
+    }
+
+    let format = match ImageFormat::from_path(path) {
+        Ok(format) => format,
+        Err(_) => image::guess_format(&buffer).map_err(CrtError::decode)?,
+    };
+
+    if let Some(frames) = decode_animation(&buffer, format)? {
+        return Ok(Input::Animated(frames));
+    }
+
+    return image::load_from_memory_with_format(&buffer, format)
+        .map(Input::Still)
+        .map_err(CrtError::decode);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::slice;
+
+    fn sample_tree(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("crt-{}-{}", name, std::process::id()));
+        fs::create_dir_all(root.join("sub")).unwrap();
+
+        for file in ["b.png", "a.jpg", "notes.txt", "sub/c.png"] {
+            fs::write(root.join(file), b"").unwrap();
+        }
+
+        return root;
+    }
+
+    #[test]
+    fn directories_list_images_in_order() {
+        let root = sample_tree("directories");
+
+        assert_eq!(
+            collect_inputs(slice::from_ref(&root), false).unwrap(),
+            [root.join("a.jpg"), root.join("b.png")]
+        );
+        assert_eq!(
+            collect_inputs(slice::from_ref(&root), true).unwrap(),
+            [
+                root.join("a.jpg"),
+                root.join("b.png"),
+                root.join("sub/c.png")
+            ]
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn globs_expand_to_matching_images() {
+        let root = sample_tree("globs");
+        let pattern = |pattern: &str| root.join(pattern);
+
+        assert_eq!(
+            collect_inputs(&[pattern("*.png"), pattern("**/*.png")], false).unwrap(),
+            [
+                root.join("b.png"),
+                root.join("b.png"),
+                root.join("sub/c.png")
+            ]
+        );
+        assert!(collect_inputs(&[pattern("*.gif")], false)
+            .unwrap()
+            .is_empty());
+        assert!(collect_inputs(&[pattern("[")], false).is_err());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_names_are_kept() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let path = PathBuf::from(OsStr::from_bytes(b"caf\xe9.png"));
+
+        assert_eq!(
+            collect_inputs(slice::from_ref(&path), false).unwrap(),
+            [path]
+        );
+    }
+}
@@ -0,0 +1,26 @@
+#![allow(clippy::needless_return)]
+
+pub mod alpha;
+pub mod animation;
+pub mod bloom;
+pub mod builder;
+pub mod color;
+pub mod detect;
+mod dimensions;
+pub mod error;
+pub mod filter;
+pub mod geometry;
+pub mod mask;
+pub mod noise;
+pub mod output;
+mod parallel;
+pub mod pipeline;
+pub mod preset;
+mod resample;
+pub mod scanlines;
+pub mod settings;
+pub mod vignette;
+
+pub use error::CrtError;
+pub use filter::CrtFilter;
+pub use settings::{CrtSettings, SettingsError};
@@ -1,218 +1,295 @@
-use image::{
-    codecs::png::PngEncoder,
-    imageops::{
-        blur,
-        colorops::{brighten_in_place, contrast_in_place},
-        resize, FilterType,
+#![allow(clippy::needless_return)]
+
+mod inputs;
+
+use clap::{CommandFactory, ErrorKind, Parser, ValueEnum};
+use crt::{
+    animation::write_animation,
+    output::{
+        default_output_path, save_animation, save_image, write_image, BitDepth, Encoding,
+        OutputFormat, PngCompression,
     },
-    ColorType, ImageBuffer, ImageEncoder, Rgb,
+    preset::Preset,
+    settings::Look,
+    CrtError, CrtFilter, SettingsError,
+};
+use image::DynamicImage;
+use inputs::{collect_inputs, load_image, Input};
+use rayon::{prelude::*, ThreadPoolBuilder};
+use std::{
+    collections::HashMap,
+    error::Error,
+    io::{self, Cursor, Write},
+    path::{Path, PathBuf},
 };
-use clap::Parser;
-use std::{error::Error, f64::consts::PI, fs::File, io::BufWriter, path::Path};
 
 #[derive(Parser)]
 #[clap(about)]
 struct Configuration {
+    /// Image files, directories or glob patterns to process, `-` for stdin
+    #[clap(
+        short,
+        long,
+        required_unless_present = "dump-config",
+        multiple_values = true,
+        value_parser = clap::value_parser!(PathBuf)
+    )]
+    image: Vec<PathBuf>,
+
+    /// Also process images in subdirectories of directory inputs
     #[clap(short, long)]
-    image: String,
+    recursive: bool,
 
+    /// Number of worker threads [default: number of CPUs]
     #[clap(short, long)]
-    directory: String,
+    jobs: Option<usize>,
 
-    #[clap(short, long, default_value_t = 2)]
-    upsampling: u32,
+    #[clap(
+        short,
+        long,
+        required_unless_present_any = ["dump-config", "output"],
+        value_parser = clap::value_parser!(PathBuf)
+    )]
+    directory: Option<PathBuf>,
 
-    #[clap(short, long)]
-    pixel: u32,
+    /// Explicit output file for a single input image, `-` for stdout
+    #[clap(
+        short,
+        long,
+        conflicts_with = "directory",
+        value_parser = clap::value_parser!(PathBuf)
+    )]
+    output: Option<PathBuf>,
 
-    #[clap(short, long)]
-    scanlines: usize,
+    /// Output image format [default: inferred from --output, otherwise png]
+    #[clap(short, long, value_enum)]
+    format: Option<OutputFormat>,
 
-    #[clap(short, long)]
-    brightness: i32,
+    /// JPEG quality, 1 to 100
+    #[clap(short, long, default_value_t = 90, value_parser = clap::value_parser!(u8).range(1..=100))]
+    quality: u8,
 
-    #[clap(short, long)]
-    contrast: f32,
+    #[clap(long, value_enum, default_value_t = PngCompression::Fast)]
+    png_compression: PngCompression,
+
+    /// Bits per output channel; 32 is linear float [default: closest to the input]
+    #[clap(long, value_enum)]
+    bit_depth: Option<BitDepth>,
+
+    #[clap(long, value_enum)]
+    preset: Option<Preset>,
+
+    /// TOML or JSON file with filter settings, overridden by explicit flags
+    #[clap(long)]
+    config: Option<String>,
+
+    /// Print the effective settings as TOML and exit
+    #[clap(long)]
+    dump_config: bool,
+
+    #[clap(flatten)]
+    look: Look,
 }
 
-fn apply_mask(
-    image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
-    pixel_size: u32,
-    red_repr: Rgb<u8>,
-    green_repr: Rgb<u8>,
-    blue_repr: Rgb<u8>,
-    amplification: u32,
-) {
-    let mut offset: u32;
-    let one_third = pixel_size / 3;
-    let two_thirds = 2 * pixel_size / 3;
-    let gap_half = (0.05 * pixel_size as f64).round() as u32;
-
-    for (x, y, pixel) in image.enumerate_pixels_mut() {
-        offset = if x % (2 * pixel_size) > pixel_size {
-            pixel_size / 2
-        } else {
-            0
-        };
-
-        if x % pixel_size > gap_half
-            && x % pixel_size < one_third - gap_half
-            && (y + offset) % pixel_size > gap_half
-            && (y + offset) % pixel_size < pixel_size - gap_half
-        {
-            *pixel = Rgb([
-                if pixel[0] as u32 * red_repr[0] as u32 / 256 + amplification < 256 {
-                    (pixel[0] as u32 * red_repr[0] as u32 / 256) as u8
-                } else {
-                    255
-                },
-                if pixel[0] as u32 * red_repr[1] as u32 / 256 + amplification < 256 {
-                    (pixel[0] as u32 * red_repr[1] as u32 / 256) as u8
-                } else {
-                    255
-                },
-                if pixel[0] as u32 * red_repr[2] as u32 / 256 + amplification < 256 {
-                    (pixel[0] as u32 * red_repr[2] as u32 / 256) as u8
-                } else {
-                    255
-                },
-            ]);
-        } else if x % pixel_size > one_third + gap_half
-            && x % pixel_size < two_thirds - gap_half
-            && (y + offset) % pixel_size > gap_half
-            && (y + offset) % pixel_size < pixel_size - gap_half
-        {
-            *pixel = Rgb([
-                if pixel[1] as u32 * green_repr[0] as u32 / 256 + amplification < 256 {
-                    (pixel[1] as u32 * green_repr[0] as u32 / 256) as u8
-                } else {
-                    255
-                },
-                if pixel[1] as u32 * green_repr[1] as u32 / 256 + amplification < 256 {
-                    (pixel[1] as u32 * green_repr[1] as u32 / 256) as u8
-                } else {
-                    255
-                },
-                if pixel[1] as u32 * green_repr[2] as u32 / 256 + amplification < 256 {
-                    (pixel[1] as u32 * green_repr[2] as u32 / 256) as u8
-                } else {
-                    255
-                },
-            ]);
-        } else if x % pixel_size > two_thirds + gap_half
-            && x % pixel_size < pixel_size - gap_half
-            && (y + offset) % pixel_size > gap_half
-            && (y + offset) % pixel_size < pixel_size - gap_half
-        {
-            *pixel = Rgb([
-                if pixel[2] as u32 * blue_repr[0] as u32 / 256 + amplification < 256 {
-                    (pixel[2] as u32 * blue_repr[0] as u32 / 256) as u8
-                } else {
-                    255
-                },
-                if pixel[2] as u32 * blue_repr[1] as u32 / 256 + amplification < 256 {
-                    (pixel[2] as u32 * blue_repr[1] as u32 / 256) as u8
-                } else {
-                    255
-                },
-                if pixel[2] as u32 * blue_repr[2] as u32 / 256 + amplification < 256 {
-                    (pixel[2] as u32 * blue_repr[2] as u32 / 256) as u8
-                } else {
-                    255
-                },
-            ]);
-        } else {
-            *pixel = Rgb([0, 0, 0]);
+fn process_image(
+    image_path: &Path,
+    output_path: &Path,
+    filter: &CrtFilter,
+    encoding: &Encoding,
+) -> Result<(), CrtError> {
+    let to_stdout = output_path == Path::new("-");
+    // Encoding into memory first lets encoders that need to seek write to a pipe too.
+    let mut buffer = Cursor::new(Vec::new());
+
+    let input = match load_image(image_path)? {
+        Input::Animated(mut frames) if !encoding.format.supports_animation() => {
+            eprintln!(
+                "{}: {} output cannot animate, keeping only the first frame",
+                image_path.display(),
+                encoding.format.extension()
+            );
+
+            Input::Still(DynamicImage::ImageRgba8(
+                frames.swap_remove(0).into_buffer(),
+            ))
         }
-    }
-}
+        input => input,
+    };
 
-fn apply_scanlines(image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, number: usize) {
-    let (_, res_y) = image.dimensions();
-    let density = number as f64 / res_y as f64;
-    let mut factor: f64;
+    match input {
+        Input::Still(image) => {
+            let bit_depth = encoding.bit_depth_for(image.color());
+            let image = filter.process_to_depth(image, bit_depth);
 
-    for (_, y, pixel) in image.enumerate_pixels_mut() {
-        factor = 0.3 * (PI * density * y as f64).sin().powi(2) + 0.7;
+            if to_stdout {
+                write_image(&mut buffer, &image, encoding).map_err(CrtError::encode)?;
+            } else {
+                save_image(output_path, &image, encoding)?;
+            }
+        }
+        Input::Animated(frames) => {
+            let frames = filter.process_frames(frames);
 
-        *pixel = Rgb([
-            (pixel[0] as f64 * factor) as u8,
-            (pixel[1] as f64 * factor) as u8,
-            (pixel[2] as f64 * factor) as u8,
-        ])
+            if to_stdout {
+                write_animation(&mut buffer, frames, encoding)?;
+            } else {
+                save_animation(output_path, frames, encoding)?;
+            }
+        }
     }
-}
 
-fn process_image(
-    image_path: &str,
-    output_directory: &str,
-    upsampling: u32,
-    pixel_size: u32,
-    red_repr: Rgb<u8>,
-    green_repr: Rgb<u8>,
-    blue_repr: Rgb<u8>,
-    scanlines: usize,
-    brightness: i32,
-    contrast: f32,
-) -> Result<(), Box<dyn Error>> {
-    let image_generic = image::open(Path::new(image_path))?;
-    let image = image_generic.into_rgb8();
-    let (res_x, res_y) = image.dimensions();
-
-    let upsampled_image = resize(
-        &image,
-        res_x * upsampling,
-        res_y * upsampling,
-        FilterType::CatmullRom,
-    );
-    let mut upsampled_image_blurred = blur(&upsampled_image, 2.0 * upsampling as f32);
-
-    apply_mask(
-        &mut upsampled_image_blurred,
-        pixel_size,
-        red_repr,
-        green_repr,
-        blue_repr,
-        40,
-    );
-
-    let mut image_with_mask = blur(&upsampled_image_blurred, 2.0 * upsampling as f32);
-    apply_scanlines(&mut image_with_mask, scanlines);
-    let mut processed_image = resize(&image_with_mask, res_x, res_y, FilterType::CatmullRom);
-    brighten_in_place(&mut processed_image, brightness);
-    contrast_in_place(&mut processed_image, contrast);
-
-    let file_name = Path::new(&image_path)
-        .file_stem()
-        .unwrap()
-        .to_str()
-        .unwrap();
-
-    let processed_image_file = File::create(Path::new(&format!(
-        "{}/{}.png",
-        output_directory, file_name
-    )))?;
-
-    let image_encoder = PngEncoder::new(BufWriter::new(processed_image_file));
-    image_encoder.write_image(&processed_image, res_x, res_y, ColorType::Rgb8)?;
+    if to_stdout {
+        io::stdout().lock().write_all(buffer.get_ref())?;
+    }
 
     return Ok(());
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let config = Configuration::parse();
-    process_image(
-        &config.image,
-        &config.directory,
-        config.upsampling,
-        config.pixel,
-        Rgb([255, 0, 0]),
-        Rgb([0, 255, 0]),
-        Rgb([0, 0, 255]),
-        config.scanlines,
-        config.brightness,
-        config.contrast,
-    )?;
+    let mut look = config.look;
+
+    if let Some(path) = &config.config {
+        look = look.or(Look::open(Path::new(path))
+            .map_err(|error| format!("failed to load config {}: {}", path, error))?);
+    }
+
+    if let Some(preset) = config.preset {
+        look = look.or(preset.look());
+    }
+
+    let settings = look.resolve().unwrap_or_else(|error| match error {
+        SettingsError::Missing(name) => Configuration::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                format!(
+                    "--{} is required unless a --preset or --config provides it",
+                    name
+                ),
+            )
+            .exit(),
+        SettingsError::OutOfRange { .. } => Configuration::command()
+            .error(ErrorKind::ValueValidation, error)
+            .exit(),
+    });
+
+    if config.dump_config {
+        print!("{}", toml::to_string(&settings)?);
+        return Ok(());
+    }
+
+    let format = match (config.format, &config.output) {
+        (Some(format), _) => format,
+        (None, Some(output)) if output == Path::new("-") => OutputFormat::Png,
+        (None, Some(output)) => OutputFormat::from_path(output).ok_or_else(|| {
+            format!(
+                "cannot infer the image format of {}, pass --format",
+                output.display()
+            )
+        })?,
+        (None, None) => OutputFormat::Png,
+    };
+    let encoding = Encoding {
+        format,
+        quality: config.quality,
+        png_compression: config.png_compression,
+        bit_depth: config.bit_depth,
+    };
+
+    if let Some(bit_depth) = config.bit_depth {
+        if !format.supports(bit_depth) {
+            return Err(format!(
+                "{} output does not support --bit-depth {}",
+                format.extension(),
+                bit_depth.to_possible_value().unwrap().get_name()
+            )
+            .into());
+        }
+    }
+    let filter = CrtFilter::new(settings)?;
+    let inputs = collect_inputs(&config.image, config.recursive)?;
+
+    if config.output.is_some() && inputs.len() != 1 {
+        return Err(format!(
+            "--output needs exactly one input image, got {}",
+            inputs.len()
+        )
+        .into());
+    }
+
+    if let Some(jobs) = config.jobs {
+        ThreadPoolBuilder::new().num_threads(jobs).build_global()?;
+    }
+
+    let output_paths = inputs
+        .iter()
+        .map(|image_path| match &config.output {
+            Some(output) => Ok(output.clone()),
+            None if image_path == Path::new("-") => Err(CrtError::InvalidPath(
+                "an image read from stdin needs an explicit --output".to_string(),
+            )),
+            None => default_output_path(
+                image_path,
+                config.directory.as_deref().unwrap_or(Path::new(".")),
+                format,
+            ),
+        })
+        .collect::<Vec<Result<PathBuf, CrtError>>>();
+    let mut writers: HashMap<&Path, Vec<&Path>> = HashMap::new();
+
+    for (image_path, output_path) in inputs.iter().zip(&output_paths) {
+        if let Ok(output_path) = output_path {
+            writers.entry(output_path).or_default().push(image_path);
+        }
+    }
+
+    let mut collisions = writers
+        .into_iter()
+        .filter(|(_, image_paths)| image_paths.len() > 1)
+        .map(|(output_path, image_paths)| {
+            let image_paths = image_paths
+                .iter()
+                .map(|image_path| image_path.display().to_string())
+                .collect::<Vec<String>>();
+
+            format!(
+                "{} would all be written to {}",
+                image_paths.join(", "),
+                output_path.display()
+            )
+        })
+        .collect::<Vec<String>>();
+
+    if !collisions.is_empty() {
+        collisions.sort();
+
+        for collision in &collisions {
+            eprintln!("{}", collision);
+        }
+
+        return Err("conflicting output names, nothing was written".into());
+    }
+
+    let failures = inputs
+        .par_iter()
+        .zip(output_paths)
+        .map(|(image_path, output_path)| {
+            let result = output_path.and_then(|output_path| {
+                process_image(image_path, &output_path, &filter, &encoding)
+            });
+
+            if let Err(error) = &result {
+                eprintln!("{}: {}", image_path.display(), error);
+            }
+
+            result.is_err()
+        })
+        .filter(|&failed| failed)
+        .count();
+
+    if failures > 0 {
+        return Err(format!("{} of {} images failed", failures, inputs.len()).into());
+    }
 
     return Ok(());
 }
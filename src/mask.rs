@@ -0,0 +1,326 @@
+use crate::{color::linearize, parallel::par_for_each_pixel};
+use image::{
+    imageops::{resize, FilterType},
+    ImageBuffer, ImageError, Rgb,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phosphor {
+    Red,
+    Green,
+    Blue,
+}
+
+pub trait Mask: Sync {
+    fn phosphor_at(&self, x: u32, y: u32) -> Option<Phosphor>;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum MaskType {
+    ApertureGrille,
+    SlotMask,
+    DotTriad,
+}
+
+impl MaskType {
+    pub fn generator(self, pixel_size: u32) -> Box<dyn Mask> {
+        match self {
+            MaskType::ApertureGrille => Box::new(ApertureGrille::new(pixel_size)),
+            MaskType::SlotMask => Box::new(SlotMask::new(pixel_size)),
+            MaskType::DotTriad => Box::new(DotTriad::new(pixel_size)),
+        }
+    }
+}
+
+struct Stripes {
+    pixel_size: u32,
+    one_third: u32,
+    two_thirds: u32,
+    gap_half: u32,
+}
+
+impl Stripes {
+    fn new(pixel_size: u32) -> Stripes {
+        return Stripes {
+            pixel_size,
+            one_third: pixel_size / 3,
+            two_thirds: 2 * pixel_size / 3,
+            gap_half: (0.05 * pixel_size as f64).round() as u32,
+        };
+    }
+
+    fn phosphor_at(&self, x: u32) -> Option<Phosphor> {
+        let column = x % self.pixel_size;
+
+        if column > self.gap_half && column < self.one_third - self.gap_half {
+            Some(Phosphor::Red)
+        } else if column > self.one_third + self.gap_half
+            && column < self.two_thirds - self.gap_half
+        {
+            Some(Phosphor::Green)
+        } else if column > self.two_thirds + self.gap_half
+            && column < self.pixel_size - self.gap_half
+        {
+            Some(Phosphor::Blue)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct ApertureGrille {
+    stripes: Stripes,
+}
+
+impl ApertureGrille {
+    pub fn new(pixel_size: u32) -> ApertureGrille {
+        return ApertureGrille {
+            stripes: Stripes::new(pixel_size),
+        };
+    }
+}
+
+impl Mask for ApertureGrille {
+    fn phosphor_at(&self, x: u32, _: u32) -> Option<Phosphor> {
+        return self.stripes.phosphor_at(x);
+    }
+}
+
+pub struct SlotMask {
+    stripes: Stripes,
+}
+
+impl SlotMask {
+    pub fn new(pixel_size: u32) -> SlotMask {
+        return SlotMask {
+            stripes: Stripes::new(pixel_size),
+        };
+    }
+}
+
+impl Mask for SlotMask {
+    fn phosphor_at(&self, x: u32, y: u32) -> Option<Phosphor> {
+        let stripes = &self.stripes;
+        let offset = if x % (2 * stripes.pixel_size) > stripes.pixel_size {
+            stripes.pixel_size / 2
+        } else {
+            0
+        };
+        let row = (y + offset) % stripes.pixel_size;
+
+        if row > stripes.gap_half && row < stripes.pixel_size - stripes.gap_half {
+            stripes.phosphor_at(x)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct DotTriad {
+    pitch: f64,
+    row_height: f64,
+    radius: f64,
+}
+
+impl DotTriad {
+    pub fn new(pixel_size: u32) -> DotTriad {
+        let pitch = pixel_size as f64 / 3.0;
+
+        return DotTriad {
+            pitch,
+            row_height: pitch * 3f64.sqrt() / 2.0,
+            radius: 0.45 * pitch,
+        };
+    }
+}
+
+impl Mask for DotTriad {
+    fn phosphor_at(&self, x: u32, y: u32) -> Option<Phosphor> {
+        let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+        let center_row = (py / self.row_height).round() as i64;
+        let mut nearest: Option<(f64, i64, i64)> = None;
+
+        // Dots sit on a triangular lattice, so the closest one may belong to a neighbouring row.
+        for row in center_row - 1..=center_row + 1 {
+            let row_offset = row as f64 * self.pitch / 2.0;
+            let column = ((px - row_offset) / self.pitch).round() as i64;
+            let dx = px - (column as f64 * self.pitch + row_offset);
+            let dy = py - row as f64 * self.row_height;
+            let distance = (dx * dx + dy * dy).sqrt();
+
+            if nearest.is_none_or(|(best, _, _)| distance < best) {
+                nearest = Some((distance, column, row));
+            }
+        }
+
+        match nearest {
+            Some((distance, column, row)) if distance < self.radius => {
+                match (column - row).rem_euclid(3) {
+                    0 => Some(Phosphor::Red),
+                    1 => Some(Phosphor::Green),
+                    _ => Some(Phosphor::Blue),
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+pub struct MaskTile {
+    tile: ImageBuffer<Rgb<f32>, Vec<f32>>,
+}
+
+impl MaskTile {
+    /// Loads a tile covering one mask period and scales it to `pixel_size` wide, keeping its
+    /// aspect. Masks are applied to the upsampled frame, so `pixel_size` is already in upsampled
+    /// pixels and the upsampling factor needs no separate scaling.
+    pub fn open(path: &Path, pixel_size: u32) -> Result<MaskTile, ImageError> {
+        let tile = linearize(image::open(path)?, None);
+        let (tile_x, tile_y) = tile.dimensions();
+        let scaled_y = ((tile_y as f64 * pixel_size as f64 / tile_x as f64).round() as u32).max(1);
+
+        return Ok(MaskTile {
+            tile: resize(&tile, pixel_size, scaled_y, FilterType::CatmullRom),
+        });
+    }
+
+    pub fn apply(&self, image: &mut ImageBuffer<Rgb<f32>, Vec<f32>>) {
+        let (tile_x, tile_y) = self.tile.dimensions();
+
+        par_for_each_pixel(image, |x, y, pixel| {
+            let weight = self.tile.get_pixel(x % tile_x, y % tile_y);
+
+            *pixel = Rgb([
+                pixel[0] * weight[0],
+                pixel[1] * weight[1],
+                pixel[2] * weight[2],
+            ]);
+        });
+    }
+}
+
+pub fn apply_mask(
+    image: &mut ImageBuffer<Rgb<f32>, Vec<f32>>,
+    mask: &dyn Mask,
+    red_repr: Rgb<f32>,
+    green_repr: Rgb<f32>,
+    blue_repr: Rgb<f32>,
+    amplification: f32,
+) {
+    let excite = |value: f32, repr: Rgb<f32>| {
+        Rgb(repr.0.map(|component| {
+            if value * component + amplification < 1.0 {
+                value * component
+            } else {
+                1.0
+            }
+        }))
+    };
+
+    par_for_each_pixel(image, |x, y, pixel| {
+        *pixel = match mask.phosphor_at(x, y) {
+            Some(Phosphor::Red) => excite(pixel[0], red_repr),
+            Some(Phosphor::Green) => excite(pixel[1], green_repr),
+            Some(Phosphor::Blue) => excite(pixel[2], blue_repr),
+            None => Rgb([0.0, 0.0, 0.0]),
+        };
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::RgbImage;
+    use Phosphor::{Blue, Green, Red};
+
+    const STRIPES: [Option<Phosphor>; 9] = [
+        None,
+        Some(Red),
+        Some(Red),
+        None,
+        Some(Green),
+        Some(Green),
+        None,
+        Some(Blue),
+        Some(Blue),
+    ];
+
+    #[test]
+    fn aperture_grille_has_no_vertical_gaps() {
+        let mask = ApertureGrille::new(9);
+
+        for y in 0..20 {
+            for x in 0..18 {
+                assert_eq!(mask.phosphor_at(x, y), STRIPES[x as usize % 9]);
+            }
+        }
+    }
+
+    #[test]
+    fn slot_mask_staggers_every_other_column_group() {
+        let mask = SlotMask::new(9);
+        let gaps = |x: u32| {
+            (0..18)
+                .filter(|&y| mask.phosphor_at(x, y).is_none())
+                .collect::<Vec<u32>>()
+        };
+
+        assert_eq!(mask.phosphor_at(1, 1), Some(Red));
+        assert_eq!(gaps(1), [0, 9]);
+        assert_eq!(gaps(10), [5, 14]);
+        assert_eq!(gaps(3).len(), 18);
+    }
+
+    #[test]
+    fn dot_triads_never_touch_their_own_color() {
+        let mask = DotTriad::new(30);
+        let dot = |column: i64, row: i64| {
+            let x = column as f64 * mask.pitch + row as f64 * mask.pitch / 2.0;
+            let y = row as f64 * mask.row_height;
+            mask.phosphor_at(x as u32, y as u32)
+        };
+
+        for row in 1..5 {
+            for column in 1..6 {
+                let color = dot(column, row);
+                let neighbours = [(1, 0), (-1, 0), (0, 1), (0, -1), (-1, 1), (1, -1)];
+
+                assert!(color.is_some());
+                for (dx, dy) in neighbours {
+                    assert!(dot(column + dx, row + dy) != color);
+                }
+            }
+        }
+
+        // Halfway between two dots of a row there is no phosphor.
+        assert_eq!(mask.phosphor_at(35, 0), None);
+    }
+
+    #[test]
+    fn tiles_repeat_every_pixel_size() {
+        let path = std::env::temp_dir().join(format!("crt-tile-{}.png", std::process::id()));
+        RgbImage::from_fn(3, 1, |x, _| {
+            let mut channels = [0; 3];
+            channels[x as usize] = 255;
+            Rgb(channels)
+        })
+        .save(&path)
+        .unwrap();
+
+        let tile = MaskTile::open(&path, 6).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut image = ImageBuffer::from_pixel(24, 4, Rgb([1.0, 1.0, 1.0]));
+        tile.apply(&mut image);
+
+        assert_eq!(tile.tile.dimensions(), (6, 2));
+        for x in 0..18 {
+            assert_eq!(image.get_pixel(x, 0), image.get_pixel(x + 6, 3));
+        }
+        assert!(image.get_pixel(0, 0)[0] > image.get_pixel(0, 0)[2]);
+        assert!(image.get_pixel(5, 1)[2] > image.get_pixel(5, 1)[0]);
+    }
+}
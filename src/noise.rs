@@ -0,0 +1,10 @@
+use crate::parallel::par_for_each_pixel;
+use image::{ImageBuffer, Rgb};
+
+pub fn apply_noise(image: &mut ImageBuffer<Rgb<f32>, Vec<f32>>, strength: f32) {
+    par_for_each_pixel(image, |_, _, pixel| {
+        let grain = 1.0 + strength * (2.0 * rand::random::<f32>() - 1.0);
+
+        *pixel = Rgb(pixel.0.map(|channel| channel * grain));
+    });
+}
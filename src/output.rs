@@ -0,0 +1,240 @@
+use crate::{animation::write_animation, error::CrtError};
+use image::{
+    codecs::{
+        bmp::BmpEncoder,
+        gif::GifEncoder,
+        jpeg::JpegEncoder,
+        openexr::OpenExrEncoder,
+        png::{self, PngEncoder},
+        tiff::TiffEncoder,
+        webp::WebPEncoder,
+    },
+    ColorType, DynamicImage, Frame, ImageEncoder, ImageResult,
+};
+use std::{
+    fs,
+    io::{self, Cursor, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum OutputFormat {
+    Png,
+    Jpeg,
+    Webp,
+    Tiff,
+    Bmp,
+    Exr,
+    Gif,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum BitDepth {
+    #[cfg_attr(feature = "cli", clap(name = "8"))]
+    Eight,
+    #[cfg_attr(feature = "cli", clap(name = "16"))]
+    Sixteen,
+    #[cfg_attr(feature = "cli", clap(name = "32"))]
+    Float,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum PngCompression {
+    Fast,
+    Balanced,
+    Best,
+}
+
+pub struct Encoding {
+    pub format: OutputFormat,
+    pub quality: u8,
+    pub png_compression: PngCompression,
+    pub bit_depth: Option<BitDepth>,
+}
+
+impl BitDepth {
+    pub fn of(color: ColorType) -> BitDepth {
+        match color {
+            ColorType::L16 | ColorType::La16 | ColorType::Rgb16 | ColorType::Rgba16 => {
+                return BitDepth::Sixteen
+            }
+            ColorType::Rgb32F | ColorType::Rgba32F => return BitDepth::Float,
+            _ => return BitDepth::Eight,
+        }
+    }
+
+    pub fn convert(self, image: DynamicImage) -> DynamicImage {
+        let has_alpha = image.color().has_alpha();
+
+        match (self, has_alpha) {
+            (BitDepth::Eight, false) => return DynamicImage::ImageRgb8(image.into_rgb8()),
+            (BitDepth::Eight, true) => return DynamicImage::ImageRgba8(image.into_rgba8()),
+            (BitDepth::Sixteen, false) => return DynamicImage::ImageRgb16(image.into_rgb16()),
+            (BitDepth::Sixteen, true) => return DynamicImage::ImageRgba16(image.into_rgba16()),
+            (BitDepth::Float, false) => return DynamicImage::ImageRgb32F(image.into_rgb32f()),
+            (BitDepth::Float, true) => return DynamicImage::ImageRgba32F(image.into_rgba32f()),
+        }
+    }
+}
+
+impl Encoding {
+    /// Picks the requested bit depth, or the one closest to the input that the format can store.
+    pub fn bit_depth_for(&self, input: ColorType) -> BitDepth {
+        if let Some(bit_depth) = self.bit_depth {
+            return bit_depth;
+        }
+
+        return [
+            BitDepth::of(input),
+            BitDepth::Sixteen,
+            BitDepth::Eight,
+            BitDepth::Float,
+        ]
+        .into_iter()
+        .find(|&bit_depth| self.format.supports(bit_depth))
+        .unwrap_or(BitDepth::Eight);
+    }
+}
+
+impl OutputFormat {
+    pub fn from_path(path: &Path) -> Option<OutputFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+
+        match extension.as_str() {
+            "png" => return Some(OutputFormat::Png),
+            "jpg" | "jpeg" => return Some(OutputFormat::Jpeg),
+            "webp" => return Some(OutputFormat::Webp),
+            "tif" | "tiff" => return Some(OutputFormat::Tiff),
+            "bmp" => return Some(OutputFormat::Bmp),
+            "exr" => return Some(OutputFormat::Exr),
+            "gif" => return Some(OutputFormat::Gif),
+            _ => return None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Png => return "png",
+            OutputFormat::Jpeg => return "jpg",
+            OutputFormat::Webp => return "webp",
+            OutputFormat::Tiff => return "tiff",
+            OutputFormat::Bmp => return "bmp",
+            OutputFormat::Exr => return "exr",
+            OutputFormat::Gif => return "gif",
+        }
+    }
+
+    pub fn supports_animation(self) -> bool {
+        return matches!(self, OutputFormat::Png | OutputFormat::Gif);
+    }
+
+    pub fn supports(self, bit_depth: BitDepth) -> bool {
+        match (self, bit_depth) {
+            (OutputFormat::Exr, bit_depth) => return bit_depth == BitDepth::Float,
+            (OutputFormat::Png | OutputFormat::Tiff, bit_depth) => {
+                return bit_depth != BitDepth::Float
+            }
+            (_, bit_depth) => return bit_depth == BitDepth::Eight,
+        }
+    }
+}
+
+pub fn write_image<W: Write + Seek>(
+    writer: &mut W,
+    image: &DynamicImage,
+    encoding: &Encoding,
+) -> ImageResult<()> {
+    let (res_x, res_y) = (image.width(), image.height());
+    let (buffer, color) = (image.as_bytes(), image.color());
+
+    match encoding.format {
+        OutputFormat::Png => {
+            let compression = match encoding.png_compression {
+                PngCompression::Fast => png::CompressionType::Fast,
+                PngCompression::Balanced => png::CompressionType::Default,
+                PngCompression::Best => png::CompressionType::Best,
+            };
+
+            PngEncoder::new_with_quality(writer, compression, png::FilterType::Adaptive)
+                .write_image(buffer, res_x, res_y, color)?;
+        }
+        OutputFormat::Jpeg => {
+            // JPEG has no alpha channel, so transparency is dropped here.
+            let rgb_image = image.to_rgb8();
+
+            JpegEncoder::new_with_quality(writer, encoding.quality).write_image(
+                &rgb_image,
+                res_x,
+                res_y,
+                ColorType::Rgb8,
+            )?
+        }
+        OutputFormat::Webp => {
+            WebPEncoder::new_lossless(writer).write_image(buffer, res_x, res_y, color)?
+        }
+        OutputFormat::Tiff => TiffEncoder::new(writer).write_image(buffer, res_x, res_y, color)?,
+        OutputFormat::Bmp => BmpEncoder::new(writer).write_image(buffer, res_x, res_y, color)?,
+        OutputFormat::Exr => {
+            OpenExrEncoder::new(writer).write_image(buffer, res_x, res_y, color)?
+        }
+        OutputFormat::Gif => GifEncoder::new(writer).encode(buffer, res_x, res_y, color)?,
+    }
+
+    return Ok(());
+}
+
+pub fn default_output_path(
+    image_path: &Path,
+    directory: &Path,
+    format: OutputFormat,
+) -> Result<PathBuf, CrtError> {
+    let mut file_name = image_path
+        .file_stem()
+        .ok_or_else(|| {
+            CrtError::InvalidPath(format!(
+                "cannot derive an output name from {}",
+                image_path.display()
+            ))
+        })?
+        .to_os_string();
+    file_name.push(".");
+    file_name.push(format.extension());
+
+    return Ok(directory.join(file_name));
+}
+
+fn write_file(output_path: &Path, buffer: &[u8]) -> Result<(), CrtError> {
+    fs::write(output_path, buffer).map_err(|error| {
+        io::Error::new(
+            error.kind(),
+            format!("cannot create {}: {}", output_path.display(), error),
+        )
+    })?;
+
+    return Ok(());
+}
+
+pub fn save_image(
+    output_path: &Path,
+    image: &DynamicImage,
+    encoding: &Encoding,
+) -> Result<(), CrtError> {
+    let mut buffer = Cursor::new(Vec::new());
+    write_image(&mut buffer, image, encoding).map_err(CrtError::encode)?;
+
+    return write_file(output_path, buffer.get_ref());
+}
+
+pub fn save_animation(
+    output_path: &Path,
+    frames: Vec<Frame>,
+    encoding: &Encoding,
+) -> Result<(), CrtError> {
+    let mut buffer = Vec::new();
+    write_animation(&mut buffer, frames, encoding)?;
+
+    return write_file(output_path, &buffer);
+}
@@ -0,0 +1,24 @@
+use image::{ImageBuffer, Pixel};
+use rayon::prelude::*;
+
+pub fn par_for_each_pixel<P, F>(image: &mut ImageBuffer<P, Vec<P::Subpixel>>, operation: F)
+where
+    P: Pixel,
+    P::Subpixel: Send,
+    F: Fn(u32, u32, &mut P) + Sync,
+{
+    let row_length = image.width() as usize * P::CHANNEL_COUNT as usize;
+
+    if row_length == 0 {
+        return;
+    }
+
+    image
+        .par_chunks_mut(row_length)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, pixel) in row.chunks_exact_mut(P::CHANNEL_COUNT as usize).enumerate() {
+                operation(x as u32, y as u32, P::from_slice_mut(pixel));
+            }
+        });
+}
@@ -0,0 +1,386 @@
+use crate::{
+    bloom::apply_bloom,
+    color::{adjust_encoded, linear_color},
+    error::CrtError,
+    geometry::apply_curvature,
+    mask::{apply_mask, MaskTile},
+    noise::apply_noise,
+    resample::{par_blur, par_resize},
+    scanlines::{apply_beam_scanlines, apply_scanlines, ScanlineMode},
+    settings::CrtSettings,
+    vignette::apply_vignette,
+};
+use image::{imageops::FilterType, ImageBuffer, Luma, Rgb};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// The picture as it moves through the stages, in linear light, starting at its native size. When
+/// the alpha is kept, the color is premultiplied by it.
+pub struct Frame {
+    pub image: ImageBuffer<Rgb<f32>, Vec<f32>>,
+    pub alpha: Option<ImageBuffer<Luma<f32>, Vec<f32>>>,
+    /// The input before any tonal stage ran, which bloom and beam scanlines read. The
+    /// geometric stages warp it along with the picture so that both stay aligned.
+    pub source: ImageBuffer<Rgb<f32>, Vec<f32>>,
+    /// How much of each pixel shows the picture rather than the border, shaped by the geometric
+    /// stages. Bloom only lights the picture.
+    pub coverage: ImageBuffer<Luma<f32>, Vec<f32>>,
+    pub scanline_count: usize,
+    /// The size upsampling stretches to, which includes the pixel aspect ratio.
+    pub upsampled_size: (u32, u32),
+    pub output_size: (u32, u32),
+    /// The brightest light the output holds: 1 for integer formats, unbounded for float ones.
+    pub white: f32,
+}
+
+/// A step of the filter, run on the frame in the order of the pipeline.
+pub trait Stage: Sync {
+    fn apply(&self, frame: &mut Frame, settings: &CrtSettings);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum StageKind {
+    Upsample,
+    Blur,
+    Mask,
+    Scanlines,
+    Bloom,
+    Curvature,
+    Vignette,
+    Noise,
+    Resize,
+    Brightness,
+    Contrast,
+}
+
+impl StageKind {
+    pub fn default_order() -> Vec<StageKind> {
+        return vec![
+            StageKind::Upsample,
+            StageKind::Blur,
+            StageKind::Mask,
+            StageKind::Blur,
+            StageKind::Scanlines,
+            StageKind::Bloom,
+            StageKind::Curvature,
+            StageKind::Vignette,
+            StageKind::Noise,
+            StageKind::Resize,
+            StageKind::Brightness,
+            StageKind::Contrast,
+        ];
+    }
+
+    pub fn stage(self, settings: &CrtSettings) -> Result<Box<dyn Stage>, CrtError> {
+        match self {
+            StageKind::Upsample => return Ok(Box::new(UpsampleStage)),
+            StageKind::Blur => return Ok(Box::new(BlurStage)),
+            StageKind::Mask => return Ok(Box::new(MaskStage::new(settings)?)),
+            StageKind::Scanlines => return Ok(Box::new(ScanlineStage)),
+            StageKind::Bloom => return Ok(Box::new(BloomStage)),
+            StageKind::Curvature => return Ok(Box::new(CurvatureStage)),
+            StageKind::Vignette => return Ok(Box::new(VignetteStage)),
+            StageKind::Noise => return Ok(Box::new(NoiseStage)),
+            StageKind::Resize => return Ok(Box::new(ResizeStage)),
+            StageKind::Brightness => return Ok(Box::new(BrightnessStage)),
+            StageKind::Contrast => return Ok(Box::new(ContrastStage)),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Pipeline {
+    stages: Vec<Box<dyn Stage>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Pipeline {
+        return Pipeline::default();
+    }
+
+    pub fn from_settings(settings: &CrtSettings) -> Result<Pipeline, CrtError> {
+        let mut pipeline = Pipeline::new();
+
+        for kind in &settings.stages {
+            pipeline.push(kind.stage(settings)?);
+        }
+
+        return Ok(pipeline);
+    }
+
+    pub fn push(&mut self, stage: Box<dyn Stage>) {
+        self.stages.push(stage);
+    }
+
+    pub fn run(&self, frame: &mut Frame, settings: &CrtSettings) {
+        for stage in &self.stages {
+            stage.apply(frame, settings);
+        }
+    }
+}
+
+/// Resamples everything the frame carries, so that the stages after it stay aligned.
+fn resize_frame(frame: &mut Frame, (res_x, res_y): (u32, u32)) {
+    if frame.image.dimensions() == (res_x, res_y) {
+        return;
+    }
+
+    frame.image = par_resize(&frame.image, res_x, res_y, FilterType::CatmullRom);
+    frame.source = par_resize(&frame.source, res_x, res_y, FilterType::CatmullRom);
+    frame.coverage = par_resize(&frame.coverage, res_x, res_y, FilterType::CatmullRom);
+    frame.alpha = frame
+        .alpha
+        .take()
+        .map(|alpha| par_resize(&alpha, res_x, res_y, FilterType::CatmullRom));
+}
+
+/// Stretching before the mask keeps phosphors square while the content gets its real proportions.
+pub struct UpsampleStage;
+
+impl Stage for UpsampleStage {
+    fn apply(&self, frame: &mut Frame, _: &CrtSettings) {
+        resize_frame(frame, frame.upsampled_size);
+    }
+}
+
+pub struct BlurStage;
+
+impl Stage for BlurStage {
+    fn apply(&self, frame: &mut Frame, settings: &CrtSettings) {
+        if settings.blur == 0.0 {
+            return;
+        }
+
+        frame.image = par_blur(&frame.image, settings.blur);
+        frame.alpha = frame
+            .alpha
+            .take()
+            .map(|alpha| par_blur(&alpha, settings.blur));
+    }
+}
+
+pub struct MaskStage {
+    tile: Option<MaskTile>,
+}
+
+impl MaskStage {
+    pub fn new(settings: &CrtSettings) -> Result<MaskStage, CrtError> {
+        let tile = match &settings.mask_tile {
+            Some(path) => {
+                Some(MaskTile::open(Path::new(path), settings.pixel).map_err(CrtError::decode)?)
+            }
+            None => None,
+        };
+
+        return Ok(MaskStage { tile });
+    }
+}
+
+impl Stage for MaskStage {
+    fn apply(&self, frame: &mut Frame, settings: &CrtSettings) {
+        match &self.tile {
+            Some(tile) => tile.apply(&mut frame.image),
+            None => apply_mask(
+                &mut frame.image,
+                settings.mask.generator(settings.pixel).as_ref(),
+                linear_color(settings.red),
+                linear_color(settings.green),
+                linear_color(settings.blue),
+                settings.amplification / 255.0,
+            ),
+        }
+    }
+}
+
+pub struct ScanlineStage;
+
+impl Stage for ScanlineStage {
+    fn apply(&self, frame: &mut Frame, settings: &CrtSettings) {
+        match settings.scanline_mode {
+            ScanlineMode::Sine => apply_scanlines(&mut frame.image, frame.scanline_count),
+            ScanlineMode::Beam => apply_beam_scanlines(
+                &mut frame.image,
+                &frame.source,
+                frame.scanline_count,
+                settings.beam_min,
+                settings.beam_max,
+                settings.beam_profile,
+            ),
+        }
+    }
+}
+
+pub struct BloomStage;
+
+impl Stage for BloomStage {
+    fn apply(&self, frame: &mut Frame, settings: &CrtSettings) {
+        if settings.bloom != 0.0 {
+            apply_bloom(
+                &mut frame.image,
+                &frame.source,
+                &frame.coverage,
+                settings.bloom_threshold,
+                settings.bloom_radius,
+                settings.bloom,
+            );
+        }
+    }
+}
+
+/// A transparent border has to be black in premultiplied color.
+fn border_color(frame: &Frame, settings: &CrtSettings) -> Rgb<f32> {
+    match frame.alpha {
+        Some(_) => return Rgb([0.0, 0.0, 0.0]),
+        None => return linear_color(settings.border),
+    }
+}
+
+pub struct CurvatureStage;
+
+impl Stage for CurvatureStage {
+    fn apply(&self, frame: &mut Frame, settings: &CrtSettings) {
+        if settings.curvature_x == 0.0 && settings.curvature_y == 0.0 {
+            return;
+        }
+
+        let border = border_color(frame, settings);
+        frame.image = apply_curvature(
+            &frame.image,
+            settings.curvature_x,
+            settings.curvature_y,
+            border,
+        );
+        frame.source = apply_curvature(
+            &frame.source,
+            settings.curvature_x,
+            settings.curvature_y,
+            border,
+        );
+        frame.coverage = apply_curvature(
+            &frame.coverage,
+            settings.curvature_x,
+            settings.curvature_y,
+            Luma([0.0]),
+        );
+        frame.alpha = frame.alpha.take().map(|alpha| {
+            apply_curvature(
+                &alpha,
+                settings.curvature_x,
+                settings.curvature_y,
+                Luma([0.0]),
+            )
+        });
+    }
+}
+
+pub struct VignetteStage;
+
+impl Stage for VignetteStage {
+    fn apply(&self, frame: &mut Frame, settings: &CrtSettings) {
+        if settings.vignette == 0.0 && settings.corner_radius == 0.0 {
+            return;
+        }
+
+        let border = border_color(frame, settings);
+        apply_vignette(
+            &mut frame.image,
+            settings.vignette,
+            settings.vignette_falloff,
+            settings.corner_radius,
+            border,
+        );
+        apply_vignette(&mut frame.source, 0.0, 1.0, settings.corner_radius, border);
+        apply_vignette(
+            &mut frame.coverage,
+            0.0,
+            1.0,
+            settings.corner_radius,
+            Luma([0.0]),
+        );
+
+        if let Some(alpha) = &mut frame.alpha {
+            apply_vignette(alpha, 0.0, 1.0, settings.corner_radius, Luma([0.0]));
+        }
+    }
+}
+
+pub struct NoiseStage;
+
+impl Stage for NoiseStage {
+    fn apply(&self, frame: &mut Frame, settings: &CrtSettings) {
+        if settings.noise != 0.0 {
+            apply_noise(&mut frame.image, settings.noise);
+        }
+    }
+}
+
+pub struct ResizeStage;
+
+impl Stage for ResizeStage {
+    fn apply(&self, frame: &mut Frame, _: &CrtSettings) {
+        resize_frame(frame, frame.output_size);
+    }
+}
+
+pub struct BrightnessStage;
+
+impl Stage for BrightnessStage {
+    fn apply(&self, frame: &mut Frame, settings: &CrtSettings) {
+        if settings.brightness == 0 {
+            return;
+        }
+
+        let offset = settings.brightness as f32 / 255.0;
+        adjust_encoded(
+            &mut frame.image,
+            frame.alpha.as_ref(),
+            frame.white,
+            |value| value + offset,
+        );
+    }
+}
+
+pub struct ContrastStage;
+
+impl Stage for ContrastStage {
+    fn apply(&self, frame: &mut Frame, settings: &CrtSettings) {
+        if settings.contrast == 0.0 {
+            return;
+        }
+
+        let percent = ((100.0 + settings.contrast) / 100.0).powi(2);
+        adjust_encoded(
+            &mut frame.image,
+            frame.alpha.as_ref(),
+            frame.white,
+            |value| (value - 0.5) * percent + 0.5,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_blur_leaves_the_frame_unchanged() {
+        let image = ImageBuffer::from_fn(8, 6, |x, y| Rgb([x as f32 / 8.0, y as f32 / 6.0, 0.0]));
+        let mut frame = Frame {
+            image: image.clone(),
+            alpha: None,
+            source: image.clone(),
+            coverage: ImageBuffer::from_pixel(8, 6, Luma([1.0])),
+            scanline_count: 6,
+            upsampled_size: (8, 6),
+            output_size: (8, 6),
+            white: 1.0,
+        };
+        let settings = CrtSettings::builder().pixel(3).blur(0.0).build().unwrap();
+
+        BlurStage.apply(&mut frame, &settings);
+
+        assert_eq!(frame.image, image);
+    }
+}
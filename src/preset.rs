@@ -0,0 +1,123 @@
+use crate::{mask::MaskType, scanlines::Scanlines, settings::Look};
+use image::Rgb;
+
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Preset {
+    SonyPvm,
+    Trinitron,
+    #[cfg_attr(feature = "cli", clap(name = "arcade-15khz"))]
+    Arcade15khz,
+    ConsumerTv,
+}
+
+impl Preset {
+    pub fn look(self) -> Look {
+        match self {
+            Preset::SonyPvm => Look {
+                upsampling: Some(4),
+                pixel: Some(6),
+                scanlines: Some(Scanlines::Count(240)),
+                brightness: Some(10),
+                contrast: Some(15.0),
+                mask: Some(MaskType::ApertureGrille),
+                red: Some(Rgb([255, 0, 0])),
+                green: Some(Rgb([0, 255, 0])),
+                blue: Some(Rgb([0, 0, 255])),
+                curvature_x: Some(0.02),
+                curvature_y: Some(0.02),
+                vignette: Some(0.1),
+                corner_radius: Some(0.02),
+                bloom: Some(0.1),
+                ..Look::default()
+            },
+            Preset::Trinitron => Look {
+                upsampling: Some(3),
+                pixel: Some(9),
+                scanlines: Some(Scanlines::Count(240)),
+                brightness: Some(20),
+                contrast: Some(10.0),
+                mask: Some(MaskType::ApertureGrille),
+                red: Some(Rgb([255, 20, 0])),
+                green: Some(Rgb([0, 255, 40])),
+                blue: Some(Rgb([0, 30, 255])),
+                curvature_x: Some(0.04),
+                curvature_y: Some(0.0),
+                vignette: Some(0.15),
+                corner_radius: Some(0.03),
+                bloom: Some(0.2),
+                ..Look::default()
+            },
+            Preset::Arcade15khz => Look {
+                upsampling: Some(3),
+                pixel: Some(12),
+                scanlines: Some(Scanlines::Count(224)),
+                brightness: Some(30),
+                contrast: Some(20.0),
+                mask: Some(MaskType::DotTriad),
+                red: Some(Rgb([255, 0, 0])),
+                green: Some(Rgb([0, 255, 0])),
+                blue: Some(Rgb([0, 0, 255])),
+                curvature_x: Some(0.06),
+                curvature_y: Some(0.06),
+                vignette: Some(0.25),
+                corner_radius: Some(0.05),
+                bloom: Some(0.4),
+                ..Look::default()
+            },
+            Preset::ConsumerTv => Look {
+                upsampling: Some(2),
+                pixel: Some(12),
+                scanlines: Some(Scanlines::Count(240)),
+                brightness: Some(25),
+                contrast: Some(5.0),
+                mask: Some(MaskType::SlotMask),
+                red: Some(Rgb([255, 48, 16])),
+                green: Some(Rgb([64, 255, 0])),
+                blue: Some(Rgb([0, 24, 255])),
+                curvature_x: Some(0.05),
+                curvature_y: Some(0.04),
+                vignette: Some(0.3),
+                corner_radius: Some(0.06),
+                bloom: Some(0.3),
+                ..Look::default()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRESETS: [Preset; 4] = [
+        Preset::SonyPvm,
+        Preset::Trinitron,
+        Preset::Arcade15khz,
+        Preset::ConsumerTv,
+    ];
+
+    #[test]
+    fn presets_fill_every_required_setting() {
+        for preset in PRESETS {
+            assert!(preset.look().resolve().is_ok());
+        }
+    }
+
+    #[test]
+    fn explicit_values_override_presets() {
+        for preset in PRESETS {
+            let flags = Look {
+                pixel: Some(4),
+                brightness: Some(-5),
+                ..Look::default()
+            };
+            let settings = flags.or(preset.look()).resolve().unwrap();
+            let defaults = preset.look().resolve().unwrap();
+
+            assert_eq!((settings.pixel, settings.brightness), (4, -5));
+            assert_eq!(settings.scanlines, defaults.scanlines);
+            assert!(settings.mask == defaults.mask);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use image::{
+    imageops::{resize, FilterType},
+    ImageBuffer, Pixel,
+};
+use rayon::prelude::*;
+use std::f32::consts::PI;
+
+struct Filter<K: Fn(f32) -> f32 + Sync> {
+    kernel: K,
+    support: f32,
+}
+
+struct Taps {
+    start: usize,
+    weights: Vec<f32>,
+}
+
+// Same tap placement as `image::imageops`, so results match its single threaded resize and blur.
+fn taps<K: Fn(f32) -> f32 + Sync>(input: u32, output: u32, filter: &Filter<K>) -> Vec<Taps> {
+    let ratio = input as f32 / output as f32;
+    let scale = ratio.max(1.0);
+    let support = filter.support * scale;
+
+    return (0..output)
+        .map(|index| {
+            let center = (index as f32 + 0.5) * ratio;
+            let start = ((center - support).floor() as i64).clamp(0, input as i64 - 1) as usize;
+            let end =
+                ((center + support).ceil() as i64).clamp(start as i64 + 1, input as i64) as usize;
+            let mut weights = (start..end)
+                .map(|tap| (filter.kernel)((tap as f32 - (center - 0.5)) / scale))
+                .collect::<Vec<f32>>();
+            let sum = weights.iter().sum::<f32>();
+            weights.iter_mut().for_each(|weight| *weight /= sum);
+
+            Taps { start, weights }
+        })
+        .collect();
+}
+
+fn sample<P, K>(
+    image: &ImageBuffer<P, Vec<f32>>,
+    new_width: u32,
+    new_height: u32,
+    filter: &Filter<K>,
+) -> ImageBuffer<P, Vec<f32>>
+where
+    P: Pixel<Subpixel = f32>,
+    K: Fn(f32) -> f32 + Sync,
+{
+    let (width, height) = image.dimensions();
+    let channels = P::CHANNEL_COUNT as usize;
+    let input = image.as_raw();
+
+    if width == 0 || height == 0 || new_width == 0 || new_height == 0 {
+        return ImageBuffer::new(new_width, new_height);
+    }
+
+    let vertical_taps = taps(height, new_height, filter);
+    let mut vertical = vec![0.0; width as usize * new_height as usize * channels];
+    let row_length = width as usize * channels;
+
+    vertical
+        .par_chunks_mut(row_length)
+        .zip(&vertical_taps)
+        .for_each(|(row, taps)| {
+            for (offset, weight) in taps.weights.iter().enumerate() {
+                let source = &input[(taps.start + offset) * row_length..][..row_length];
+
+                for (value, source) in row.iter_mut().zip(source) {
+                    *value += source * weight;
+                }
+            }
+        });
+
+    let horizontal_taps = taps(width, new_width, filter);
+    let mut output = vec![0.0; new_width as usize * new_height as usize * channels];
+
+    output
+        .par_chunks_mut(new_width as usize * channels)
+        .zip(vertical.par_chunks(row_length))
+        .for_each(|(row, source)| {
+            for (pixel, taps) in row.chunks_exact_mut(channels).zip(&horizontal_taps) {
+                for (offset, weight) in taps.weights.iter().enumerate() {
+                    let source = &source[(taps.start + offset) * channels..][..channels];
+
+                    for (value, source) in pixel.iter_mut().zip(source) {
+                        *value += source * weight;
+                    }
+                }
+            }
+
+            // Catmull-Rom overshoots next to hard edges; negative light makes no sense.
+            row.iter_mut().for_each(|value| *value = value.max(0.0));
+        });
+
+    return ImageBuffer::from_raw(new_width, new_height, output).unwrap();
+}
+
+fn catmull_rom(x: f32) -> f32 {
+    let a = x.abs();
+
+    if a < 1.0 {
+        return (9.0 * a.powi(3) - 15.0 * a.powi(2) + 6.0) / 6.0;
+    } else if a < 2.0 {
+        return (-3.0 * a.powi(3) + 15.0 * a.powi(2) - 24.0 * a + 12.0) / 6.0;
+    } else {
+        return 0.0;
+    }
+}
+
+fn triangle(x: f32) -> f32 {
+    return (1.0 - x.abs()).max(0.0);
+}
+
+/// Row-parallel version of `image::imageops::resize` for the triangle and Catmull-Rom filters.
+/// Other filters fall back to the single threaded implementation.
+pub fn par_resize<P: Pixel<Subpixel = f32> + 'static>(
+    image: &ImageBuffer<P, Vec<f32>>,
+    new_width: u32,
+    new_height: u32,
+    filter: FilterType,
+) -> ImageBuffer<P, Vec<f32>> {
+    match filter {
+        FilterType::Triangle => {
+            let filter = Filter {
+                kernel: triangle,
+                support: 1.0,
+            };
+            return sample(image, new_width, new_height, &filter);
+        }
+        FilterType::CatmullRom => {
+            let filter = Filter {
+                kernel: catmull_rom,
+                support: 2.0,
+            };
+            return sample(image, new_width, new_height, &filter);
+        }
+        filter => return resize(image, new_width, new_height, filter),
+    }
+}
+
+/// Row-parallel Gaussian blur. Like `image::imageops::blur`, a sigma of 0 or less means 1.
+pub fn par_blur<P: Pixel<Subpixel = f32>>(
+    image: &ImageBuffer<P, Vec<f32>>,
+    sigma: f32,
+) -> ImageBuffer<P, Vec<f32>> {
+    let sigma = if sigma <= 0.0 { 1.0 } else { sigma };
+    let filter = Filter {
+        kernel: |x: f32| (-x * x / (2.0 * sigma * sigma)).exp() / ((2.0 * PI).sqrt() * sigma),
+        support: 2.0 * sigma,
+    };
+
+    return sample(image, image.width(), image.height(), &filter);
+}
@@ -0,0 +1,141 @@
+use crate::parallel::par_for_each_pixel;
+use image::{ImageBuffer, Rgb};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{f64::consts::PI, fmt};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scanlines {
+    Count(usize),
+    Auto,
+}
+
+pub fn parse_scanlines(value: &str) -> Result<Scanlines, String> {
+    if value == "auto" {
+        return Ok(Scanlines::Auto);
+    }
+
+    return value
+        .parse()
+        .map(Scanlines::Count)
+        .map_err(|_| format!("`{}` is neither a number of lines nor `auto`", value));
+}
+
+impl Serialize for Scanlines {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Scanlines::Count(number) => return serializer.serialize_u64(*number as u64),
+            Scanlines::Auto => return serializer.serialize_str("auto"),
+        }
+    }
+}
+
+struct ScanlinesVisitor;
+
+impl<'de> de::Visitor<'de> for ScanlinesVisitor {
+    type Value = Scanlines;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        return formatter.write_str("a number of lines or \"auto\"");
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Scanlines, E> {
+        return Ok(Scanlines::Count(value as usize));
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Scanlines, E> {
+        return usize::try_from(value)
+            .map(Scanlines::Count)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self));
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Scanlines, E> {
+        return parse_scanlines(value).map_err(E::custom);
+    }
+}
+
+impl<'de> Deserialize<'de> for Scanlines {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Scanlines, D::Error> {
+        return deserializer.deserialize_any(ScanlinesVisitor);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum ScanlineMode {
+    Sine,
+    Beam,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum BeamProfile {
+    Gaussian,
+    Sinc,
+    Flat,
+}
+
+pub fn apply_scanlines(image: &mut ImageBuffer<Rgb<f32>, Vec<f32>>, number: usize) {
+    let (_, res_y) = image.dimensions();
+    let density = number as f64 / res_y as f64;
+
+    par_for_each_pixel(image, |_, y, pixel| {
+        let factor = (0.3 * (PI * density * y as f64).sin().powi(2) + 0.7) as f32;
+
+        *pixel = Rgb([pixel[0] * factor, pixel[1] * factor, pixel[2] * factor])
+    });
+}
+
+/// Beam width follows the luminance of `source`, the picture before the mask split it into
+/// single phosphors.
+pub fn apply_beam_scanlines(
+    image: &mut ImageBuffer<Rgb<f32>, Vec<f32>>,
+    source: &ImageBuffer<Rgb<f32>, Vec<f32>>,
+    number: usize,
+    min_width: f32,
+    max_width: f32,
+    profile: BeamProfile,
+) {
+    let (_, res_y) = image.dimensions();
+    let density = number as f64 / res_y as f64;
+
+    par_for_each_pixel(image, |x, y, pixel| {
+        // Distance from the centre of the nearest scanline, in units of line spacing.
+        let distance = ((y as f64 + 0.5) * density).fract() - 0.5;
+        let color = source.get_pixel_checked(x, y).unwrap_or(pixel);
+        let luma = (0.2126 * color[0] + 0.7152 * color[1] + 0.0722 * color[2]).clamp(0.0, 1.0);
+        let width = (min_width + (max_width - min_width) * luma).max(f32::EPSILON) as f64;
+
+        let factor = match profile {
+            BeamProfile::Gaussian => {
+                let sigma = width / (8.0 * 2f64.ln()).sqrt();
+                (-distance * distance / (2.0 * sigma * sigma)).exp()
+            }
+            BeamProfile::Sinc => {
+                let x = PI * distance / width;
+                if x == 0.0 {
+                    1.0
+                } else {
+                    (x.sin() / x).max(0.0)
+                }
+            }
+            BeamProfile::Flat => ((width / 2.0 - distance.abs()) / density + 0.5).clamp(0.0, 1.0),
+        } as f32;
+
+        *pixel = Rgb([pixel[0] * factor, pixel[1] * factor, pixel[2] * factor])
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_counts_and_auto() {
+        assert_eq!(parse_scanlines("240"), Ok(Scanlines::Count(240)));
+        assert_eq!(parse_scanlines("auto"), Ok(Scanlines::Auto));
+        assert!(parse_scanlines("-1").is_err());
+        assert!(parse_scanlines("many").is_err());
+    }
+}
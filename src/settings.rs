@@ -0,0 +1,689 @@
+use crate::{
+    alpha::AlphaMode,
+    color, dimensions,
+    error::CrtError,
+    mask::MaskType,
+    pipeline::StageKind,
+    scanlines::{BeamProfile, ScanlineMode, Scanlines},
+};
+use image::Rgb;
+use serde::{Deserialize, Serialize, Serializer};
+use std::{error::Error, fmt, fs, path::Path};
+
+#[derive(Clone, Default, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::Args))]
+#[serde(default, deny_unknown_fields)]
+pub struct Look {
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub upsampling: Option<u32>,
+
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub pixel: Option<u32>,
+
+    /// Number of scanlines, or `auto` to match the source's native height
+    #[cfg_attr(
+        feature = "cli",
+        clap(short, long, value_parser = crate::scanlines::parse_scanlines)
+    )]
+    pub scanlines: Option<Scanlines>,
+
+    /// Native vertical resolution of the source, used by `--scanlines auto`
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub native_height: Option<u32>,
+
+    /// Undo integer nearest-neighbour upscaling of pixel art before filtering
+    #[cfg_attr(
+        feature = "cli",
+        clap(
+            long,
+            value_name = "BOOL",
+            min_values = 0,
+            require_equals = true,
+            default_missing_value = "true"
+        )
+    )]
+    pub downscale_native: Option<bool>,
+
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub brightness: Option<i32>,
+
+    #[cfg_attr(feature = "cli", clap(short, long))]
+    pub contrast: Option<f32>,
+
+    #[cfg_attr(feature = "cli", clap(short, long, value_enum))]
+    pub mask: Option<MaskType>,
+
+    /// Image of one mask period, scaled to --pixel wide. Like the built-in masks it is laid over
+    /// the upsampled frame, so --pixel already counts pixels after --upsampling
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub mask_tile: Option<String>,
+
+    /// Red phosphor color as #rrggbb or r,g,b
+    #[cfg_attr(feature = "cli", clap(long, value_parser = color::parse_color))]
+    #[serde(deserialize_with = "color::deserialize_some")]
+    pub red: Option<Rgb<u8>>,
+
+    /// Green phosphor color as #rrggbb or r,g,b
+    #[cfg_attr(feature = "cli", clap(long, value_parser = color::parse_color))]
+    #[serde(deserialize_with = "color::deserialize_some")]
+    pub green: Option<Rgb<u8>>,
+
+    /// Blue phosphor color as #rrggbb or r,g,b
+    #[cfg_attr(feature = "cli", clap(long, value_parser = color::parse_color))]
+    #[serde(deserialize_with = "color::deserialize_some")]
+    pub blue: Option<Rgb<u8>>,
+
+    /// Headroom below full brightness, out of 255, at which a lit phosphor saturates [default: 40]
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub amplification: Option<f32>,
+
+    /// Blur radius around the mask stage [default: 2 * upsampling]
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub blur: Option<f32>,
+
+    /// Horizontal barrel distortion of the screen [default: 0]
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub curvature_x: Option<f32>,
+
+    /// Vertical barrel distortion of the screen [default: 0]
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub curvature_y: Option<f32>,
+
+    /// Color outside the curved tube as #rrggbb or r,g,b [default: #000000]
+    #[cfg_attr(feature = "cli", clap(long, value_parser = color::parse_color))]
+    #[serde(deserialize_with = "color::deserialize_some")]
+    pub border: Option<Rgb<u8>>,
+
+    /// Darkening toward the edges of the screen, 0 to 1 [default: 0]
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub vignette: Option<f32>,
+
+    /// Exponent shaping how quickly the vignette falls off [default: 2]
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub vignette_falloff: Option<f32>,
+
+    /// Radius of the rounded screen corners as a fraction of the shorter side [default: 0]
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub corner_radius: Option<f32>,
+
+    /// Intensity of the glow added around bright areas [default: 0]
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub bloom: Option<f32>,
+
+    /// Luminance above which pixels start to glow, 0 to 1 [default: 0.7]
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub bloom_threshold: Option<f32>,
+
+    /// Blur radius of the glow [default: 8 * upsampling]
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub bloom_radius: Option<f32>,
+
+    /// Pixel aspect ratio of the source, e.g. 8:7 for NES and SNES [default: 1]
+    #[cfg_attr(
+        feature = "cli",
+        clap(long, value_parser = dimensions::parse_ratio, conflicts_with = "display-aspect")
+    )]
+    #[serde(deserialize_with = "dimensions::deserialize_ratio")]
+    pub par: Option<f32>,
+
+    /// Aspect ratio of the whole displayed picture, e.g. 4:3
+    #[cfg_attr(feature = "cli", clap(long, value_parser = dimensions::parse_ratio))]
+    #[serde(deserialize_with = "dimensions::deserialize_ratio")]
+    pub display_aspect: Option<f32>,
+
+    /// Output size relative to the input image [default: 1]
+    #[cfg_attr(feature = "cli", clap(long, conflicts_with = "output-size"))]
+    pub output_scale: Option<f32>,
+
+    /// Exact output size as WIDTHxHEIGHT
+    #[cfg_attr(feature = "cli", clap(long, value_parser = dimensions::parse_size))]
+    #[serde(deserialize_with = "dimensions::deserialize_size")]
+    pub output_size: Option<(u32, u32)>,
+
+    /// Strength of random grain added to every pixel, 0 to 1 [default: 0]
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub noise: Option<f32>,
+
+    /// Comma separated order of the stages to run
+    /// [default: upsample,blur,mask,blur,scanlines,bloom,curvature,vignette,noise,resize,brightness,contrast]
+    #[cfg_attr(feature = "cli", clap(long, value_enum, value_delimiter = ','))]
+    pub stages: Option<Vec<StageKind>>,
+
+    /// What to do with transparency in the input [default: discard]
+    #[cfg_attr(feature = "cli", clap(long, value_enum))]
+    pub alpha: Option<AlphaMode>,
+
+    /// Color to composite transparent input onto with `--alpha composite` [default: #000000]
+    #[cfg_attr(feature = "cli", clap(long, value_parser = color::parse_color))]
+    #[serde(deserialize_with = "color::deserialize_some")]
+    pub background: Option<Rgb<u8>>,
+
+    /// Emulated CRT gamma used to decode the input [default: sRGB curve]
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub gamma: Option<f32>,
+
+    #[cfg_attr(feature = "cli", clap(long, value_enum))]
+    pub scanline_mode: Option<ScanlineMode>,
+
+    /// Beam width of black lines as a fraction of line spacing [default: 0.3]
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub beam_min: Option<f32>,
+
+    /// Beam width of white lines as a fraction of line spacing [default: 0.9]
+    #[cfg_attr(feature = "cli", clap(long))]
+    pub beam_max: Option<f32>,
+
+    #[cfg_attr(feature = "cli", clap(long, value_enum))]
+    pub beam_profile: Option<BeamProfile>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct CrtSettings {
+    pub upsampling: u32,
+    pub pixel: u32,
+    pub scanlines: Scanlines,
+    pub native_height: Option<u32>,
+    pub downscale_native: bool,
+    pub brightness: i32,
+    #[serde(serialize_with = "serialize_f32")]
+    pub contrast: f32,
+    pub mask: MaskType,
+    pub mask_tile: Option<String>,
+    #[serde(serialize_with = "color::serialize")]
+    pub red: Rgb<u8>,
+    #[serde(serialize_with = "color::serialize")]
+    pub green: Rgb<u8>,
+    #[serde(serialize_with = "color::serialize")]
+    pub blue: Rgb<u8>,
+    #[serde(serialize_with = "serialize_f32")]
+    pub amplification: f32,
+    #[serde(serialize_with = "serialize_f32")]
+    pub blur: f32,
+    #[serde(serialize_with = "serialize_f32")]
+    pub curvature_x: f32,
+    #[serde(serialize_with = "serialize_f32")]
+    pub curvature_y: f32,
+    #[serde(serialize_with = "color::serialize")]
+    pub border: Rgb<u8>,
+    #[serde(serialize_with = "serialize_f32")]
+    pub vignette: f32,
+    #[serde(serialize_with = "serialize_f32")]
+    pub vignette_falloff: f32,
+    #[serde(serialize_with = "serialize_f32")]
+    pub corner_radius: f32,
+    #[serde(serialize_with = "serialize_f32")]
+    pub bloom: f32,
+    #[serde(serialize_with = "serialize_f32")]
+    pub bloom_threshold: f32,
+    #[serde(serialize_with = "serialize_f32")]
+    pub bloom_radius: f32,
+    #[serde(serialize_with = "serialize_f32")]
+    pub par: f32,
+    #[serde(
+        serialize_with = "serialize_optional_f32",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub display_aspect: Option<f32>,
+    #[serde(serialize_with = "serialize_f32")]
+    pub output_scale: f32,
+    #[serde(
+        serialize_with = "dimensions::serialize_size",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub output_size: Option<(u32, u32)>,
+    #[serde(serialize_with = "serialize_f32")]
+    pub noise: f32,
+    pub stages: Vec<StageKind>,
+    pub alpha: AlphaMode,
+    #[serde(serialize_with = "color::serialize")]
+    pub background: Rgb<u8>,
+    #[serde(
+        serialize_with = "serialize_optional_f32",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub gamma: Option<f32>,
+    pub scanline_mode: ScanlineMode,
+    #[serde(serialize_with = "serialize_f32")]
+    pub beam_min: f32,
+    #[serde(serialize_with = "serialize_f32")]
+    pub beam_max: f32,
+    pub beam_profile: BeamProfile,
+}
+
+impl Look {
+    pub fn open(path: &Path) -> Result<Look, CrtError> {
+        let contents = fs::read_to_string(path)?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => {
+                return serde_json::from_str(&contents)
+                    .map_err(|error| CrtError::Config(error.to_string()))
+            }
+            _ => {
+                return toml::from_str(&contents)
+                    .map_err(|error| CrtError::Config(error.to_string()))
+            }
+        }
+    }
+
+    pub fn or(self, fallback: Look) -> Look {
+        return Look {
+            upsampling: self.upsampling.or(fallback.upsampling),
+            pixel: self.pixel.or(fallback.pixel),
+            scanlines: self.scanlines.or(fallback.scanlines),
+            native_height: self.native_height.or(fallback.native_height),
+            downscale_native: self.downscale_native.or(fallback.downscale_native),
+            brightness: self.brightness.or(fallback.brightness),
+            contrast: self.contrast.or(fallback.contrast),
+            mask: self.mask.or(fallback.mask),
+            mask_tile: self.mask_tile.or(fallback.mask_tile),
+            red: self.red.or(fallback.red),
+            green: self.green.or(fallback.green),
+            blue: self.blue.or(fallback.blue),
+            amplification: self.amplification.or(fallback.amplification),
+            blur: self.blur.or(fallback.blur),
+            curvature_x: self.curvature_x.or(fallback.curvature_x),
+            curvature_y: self.curvature_y.or(fallback.curvature_y),
+            border: self.border.or(fallback.border),
+            vignette: self.vignette.or(fallback.vignette),
+            vignette_falloff: self.vignette_falloff.or(fallback.vignette_falloff),
+            corner_radius: self.corner_radius.or(fallback.corner_radius),
+            bloom: self.bloom.or(fallback.bloom),
+            bloom_threshold: self.bloom_threshold.or(fallback.bloom_threshold),
+            bloom_radius: self.bloom_radius.or(fallback.bloom_radius),
+            par: self.par.or(fallback.par),
+            display_aspect: self.display_aspect.or(fallback.display_aspect),
+            output_scale: self.output_scale.or(fallback.output_scale),
+            output_size: self.output_size.or(fallback.output_size),
+            noise: self.noise.or(fallback.noise),
+            stages: self.stages.or(fallback.stages),
+            alpha: self.alpha.or(fallback.alpha),
+            background: self.background.or(fallback.background),
+            gamma: self.gamma.or(fallback.gamma),
+            scanline_mode: self.scanline_mode.or(fallback.scanline_mode),
+            beam_min: self.beam_min.or(fallback.beam_min),
+            beam_max: self.beam_max.or(fallback.beam_max),
+            beam_profile: self.beam_profile.or(fallback.beam_profile),
+        };
+    }
+
+    pub fn resolve(self) -> Result<CrtSettings, SettingsError> {
+        let upsampling = self.upsampling.unwrap_or(2);
+
+        let settings = CrtSettings {
+            upsampling,
+            pixel: self.pixel.ok_or(SettingsError::Missing("pixel"))?,
+            scanlines: self.scanlines.ok_or(SettingsError::Missing("scanlines"))?,
+            native_height: self.native_height,
+            downscale_native: self.downscale_native.unwrap_or(false),
+            brightness: self
+                .brightness
+                .ok_or(SettingsError::Missing("brightness"))?,
+            contrast: self.contrast.ok_or(SettingsError::Missing("contrast"))?,
+            mask: self.mask.unwrap_or(MaskType::SlotMask),
+            mask_tile: self.mask_tile,
+            red: self.red.unwrap_or(Rgb([255, 0, 0])),
+            green: self.green.unwrap_or(Rgb([0, 255, 0])),
+            blue: self.blue.unwrap_or(Rgb([0, 0, 255])),
+            amplification: self.amplification.unwrap_or(40.0),
+            blur: self.blur.unwrap_or(2.0 * upsampling as f32),
+            curvature_x: self.curvature_x.unwrap_or(0.0),
+            curvature_y: self.curvature_y.unwrap_or(0.0),
+            border: self.border.unwrap_or(Rgb([0, 0, 0])),
+            vignette: self.vignette.unwrap_or(0.0),
+            vignette_falloff: self.vignette_falloff.unwrap_or(2.0),
+            corner_radius: self.corner_radius.unwrap_or(0.0),
+            bloom: self.bloom.unwrap_or(0.0),
+            bloom_threshold: self.bloom_threshold.unwrap_or(0.7),
+            bloom_radius: self.bloom_radius.unwrap_or(8.0 * upsampling as f32),
+            par: self.par.unwrap_or(1.0),
+            display_aspect: self.display_aspect,
+            output_scale: self.output_scale.unwrap_or(1.0),
+            output_size: self.output_size,
+            noise: self.noise.unwrap_or(0.0),
+            stages: self.stages.unwrap_or_else(StageKind::default_order),
+            alpha: self.alpha.unwrap_or(AlphaMode::Discard),
+            background: self.background.unwrap_or(Rgb([0, 0, 0])),
+            gamma: self.gamma,
+            scanline_mode: self.scanline_mode.unwrap_or(ScanlineMode::Sine),
+            beam_min: self.beam_min.unwrap_or(0.3),
+            beam_max: self.beam_max.unwrap_or(0.9),
+            beam_profile: self.beam_profile.unwrap_or(BeamProfile::Gaussian),
+        };
+
+        settings.validate()?;
+
+        return Ok(settings);
+    }
+}
+
+impl CrtSettings {
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        check(
+            "upsampling",
+            self.upsampling,
+            (1..=16).contains(&self.upsampling),
+            "between 1 and 16",
+        )?;
+        check("pixel", self.pixel, self.pixel >= 3, "at least 3")?;
+
+        if let Scanlines::Count(count) = self.scanlines {
+            check("scanlines", count, count >= 1, "at least 1")?;
+        }
+
+        if let Some(native_height) = self.native_height {
+            check(
+                "native_height",
+                native_height,
+                native_height >= 1,
+                "at least 1",
+            )?;
+        }
+
+        if let Some((width, height)) = self.output_size {
+            check(
+                "output_size",
+                format!("{}x{}", width, height),
+                width >= 1 && height >= 1,
+                "at least 1x1",
+            )?;
+        }
+
+        check(
+            "par",
+            self.par,
+            (0.1..=10.0).contains(&self.par),
+            "between 0.1 and 10",
+        )?;
+
+        if let Some(display_aspect) = self.display_aspect {
+            check(
+                "display_aspect",
+                display_aspect,
+                (0.1..=10.0).contains(&display_aspect),
+                "between 0.1 and 10",
+            )?;
+        }
+
+        check(
+            "contrast",
+            self.contrast,
+            self.contrast.is_finite(),
+            "a finite number",
+        )?;
+        check(
+            "curvature_x",
+            self.curvature_x,
+            self.curvature_x.is_finite(),
+            "a finite number",
+        )?;
+        check(
+            "curvature_y",
+            self.curvature_y,
+            self.curvature_y.is_finite(),
+            "a finite number",
+        )?;
+        check(
+            "amplification",
+            self.amplification,
+            (0.0..=255.0).contains(&self.amplification),
+            "between 0 and 255",
+        )?;
+        check(
+            "blur",
+            self.blur,
+            self.blur.is_finite() && self.blur >= 0.0,
+            "finite and at least 0",
+        )?;
+        check(
+            "vignette",
+            self.vignette,
+            (0.0..=1.0).contains(&self.vignette),
+            "between 0 and 1",
+        )?;
+        check(
+            "vignette_falloff",
+            self.vignette_falloff,
+            self.vignette_falloff.is_finite() && self.vignette_falloff > 0.0,
+            "finite and greater than 0",
+        )?;
+        check(
+            "corner_radius",
+            self.corner_radius,
+            (0.0..=0.5).contains(&self.corner_radius),
+            "between 0 and 0.5",
+        )?;
+        check(
+            "noise",
+            self.noise,
+            (0.0..=1.0).contains(&self.noise),
+            "between 0 and 1",
+        )?;
+        check(
+            "bloom",
+            self.bloom,
+            self.bloom.is_finite() && self.bloom >= 0.0,
+            "finite and at least 0",
+        )?;
+        check(
+            "bloom_threshold",
+            self.bloom_threshold,
+            (0.0..1.0).contains(&self.bloom_threshold),
+            "at least 0 and below 1",
+        )?;
+        check(
+            "bloom_radius",
+            self.bloom_radius,
+            self.bloom_radius.is_finite() && self.bloom_radius > 0.0,
+            "finite and greater than 0",
+        )?;
+        check(
+            "output_scale",
+            self.output_scale,
+            self.output_scale.is_finite() && self.output_scale > 0.0,
+            "finite and greater than 0",
+        )?;
+
+        if let Some(gamma) = self.gamma {
+            check(
+                "gamma",
+                gamma,
+                gamma.is_finite() && gamma > 0.0,
+                "finite and greater than 0",
+            )?;
+        }
+
+        check(
+            "beam_min",
+            self.beam_min,
+            self.beam_min.is_finite() && self.beam_min > 0.0,
+            "finite and greater than 0",
+        )?;
+        check(
+            "beam_max",
+            self.beam_max,
+            self.beam_max.is_finite() && self.beam_max >= self.beam_min,
+            "finite and at least beam_min",
+        )?;
+
+        return Ok(());
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SettingsError {
+    Missing(&'static str),
+    OutOfRange {
+        name: &'static str,
+        value: String,
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SettingsError::Missing(name) => return write!(formatter, "{} is required", name),
+            SettingsError::OutOfRange {
+                name,
+                value,
+                expected,
+            } => return write!(formatter, "{} is {} but must be {}", name, value, expected),
+        }
+    }
+}
+
+impl Error for SettingsError {}
+
+// Widening f32 to f64 prints noise such as 0.30000001192092896, so go through the shortest f32 text.
+fn rounded(value: f32) -> f64 {
+    return value.to_string().parse().unwrap_or(value as f64);
+}
+
+fn serialize_f32<S: Serializer>(value: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+    return serializer.serialize_f64(rounded(*value));
+}
+
+fn serialize_optional_f32<S: Serializer>(
+    value: &Option<f32>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => return serializer.serialize_f64(rounded(*value)),
+        None => return serializer.serialize_none(),
+    }
+}
+
+fn check<T: ToString>(
+    name: &'static str,
+    value: T,
+    valid: bool,
+    expected: &'static str,
+) -> Result<(), SettingsError> {
+    if valid {
+        return Ok(());
+    }
+
+    return Err(SettingsError::OutOfRange {
+        name,
+        value: value.to_string(),
+        expected,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preset::Preset;
+
+    #[test]
+    fn dumped_config_loads_back_unchanged() {
+        let look = Look {
+            pixel: Some(6),
+            display_aspect: Some(4.0 / 3.0),
+            output_size: Some((1280, 960)),
+            gamma: Some(2.4),
+            ..Preset::ConsumerTv.look()
+        };
+        let dumped = toml::to_string(&look.resolve().unwrap()).unwrap();
+        let reloaded = toml::from_str::<Look>(&dumped).unwrap().resolve().unwrap();
+
+        assert_eq!(toml::to_string(&reloaded).unwrap(), dumped);
+    }
+
+    #[test]
+    fn dumped_floats_are_rounded() {
+        let dumped = toml::to_string(&Preset::SonyPvm.look().resolve().unwrap()).unwrap();
+
+        assert!(dumped.contains("curvature_x = 0.02\n"), "{}", dumped);
+    }
+
+    #[test]
+    fn infinite_values_are_rejected() {
+        let rejected = |look: Look| match look.or(Preset::ConsumerTv.look()).resolve() {
+            Err(SettingsError::OutOfRange { name, .. }) => name,
+            _ => "",
+        };
+        let infinite = Some(f32::INFINITY);
+
+        assert_eq!(
+            rejected(Look {
+                blur: infinite,
+                ..Look::default()
+            }),
+            "blur"
+        );
+        assert_eq!(
+            rejected(Look {
+                bloom: infinite,
+                ..Look::default()
+            }),
+            "bloom"
+        );
+        assert_eq!(
+            rejected(Look {
+                gamma: infinite,
+                ..Look::default()
+            }),
+            "gamma"
+        );
+        assert_eq!(
+            rejected(Look {
+                contrast: infinite,
+                ..Look::default()
+            }),
+            "contrast"
+        );
+        assert_eq!(
+            rejected(Look {
+                beam_max: infinite,
+                ..Look::default()
+            }),
+            "beam_max"
+        );
+        assert_eq!(
+            rejected(Look {
+                par: Some(0.0001),
+                ..Look::default()
+            }),
+            "par"
+        );
+    }
+
+    #[test]
+    fn degenerate_geometry_is_rejected() {
+        let rejected = |look: Look| match look.or(Preset::ConsumerTv.look()).resolve() {
+            Err(SettingsError::OutOfRange { name, .. }) => name,
+            _ => "",
+        };
+
+        assert_eq!(
+            rejected(Look {
+                par: Some(0.0),
+                ..Look::default()
+            }),
+            "par"
+        );
+        assert_eq!(
+            rejected(Look {
+                display_aspect: Some(f32::INFINITY),
+                ..Look::default()
+            }),
+            "display_aspect"
+        );
+        assert_eq!(
+            rejected(Look {
+                curvature_x: Some(f32::NAN),
+                ..Look::default()
+            }),
+            "curvature_x"
+        );
+        assert_eq!(
+            rejected(Look {
+                upsampling: Some(1 << 20),
+                ..Look::default()
+            }),
+            "upsampling"
+        );
+    }
+}
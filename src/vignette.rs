@@ -0,0 +1,68 @@
+use crate::parallel::par_for_each_pixel;
+use image::{ImageBuffer, Pixel};
+
+pub fn apply_vignette<P: Pixel<Subpixel = f32> + Sync>(
+    image: &mut ImageBuffer<P, Vec<f32>>,
+    strength: f32,
+    falloff: f32,
+    corner_radius: f32,
+    border: P,
+) {
+    let (res_x, res_y) = image.dimensions();
+    let (half_x, half_y) = (res_x as f64 / 2.0, res_y as f64 / 2.0);
+    let radius = corner_radius as f64 * res_x.min(res_y) as f64;
+
+    par_for_each_pixel(image, |x, y, pixel| {
+        let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+        let (u, v) = (px / half_x - 1.0, py / half_y - 1.0);
+        let factor = 1.0 - strength as f64 * ((u * u + v * v) / 2.0).sqrt().powf(falloff as f64);
+
+        let dx = (radius - px).max(px - (res_x as f64 - radius)).max(0.0);
+        let dy = (radius - py).max(py - (res_y as f64 - radius)).max(0.0);
+
+        // Only pixels inside a corner square are trimmed; everything else is fully on the glass.
+        let coverage = if radius <= 0.0 || (dx == 0.0 && dy == 0.0) {
+            1.0
+        } else {
+            (radius - (dx * dx + dy * dy).sqrt() + 0.5).clamp(0.0, 1.0)
+        };
+
+        for (channel, border) in pixel.channels_mut().iter_mut().zip(border.channels()) {
+            *channel = (*channel as f64 * factor.max(0.0) * coverage
+                + *border as f64 * (1.0 - coverage)) as f32;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+
+    fn gradient() -> ImageBuffer<Rgb<f32>, Vec<f32>> {
+        return ImageBuffer::from_fn(16, 12, |x, y| Rgb([x as f32 / 16.0, y as f32 / 12.0, 0.5]));
+    }
+
+    #[test]
+    fn zero_strength_and_radius_leave_the_image_unchanged() {
+        let original = gradient();
+        let mut image = original.clone();
+
+        apply_vignette(&mut image, 0.0, 2.0, 0.0, Rgb([1.0, 0.0, 0.0]));
+
+        assert_eq!(image, original);
+    }
+
+    #[test]
+    fn rounded_corners_only_touch_the_corners() {
+        let original = gradient();
+        let mut image = original.clone();
+
+        apply_vignette(&mut image, 0.0, 2.0, 0.25, Rgb([1.0, 0.0, 0.0]));
+
+        assert_eq!(image.get_pixel(0, 0), &Rgb([1.0, 0.0, 0.0]));
+        assert_eq!(image.get_pixel(15, 11), &Rgb([1.0, 0.0, 0.0]));
+        assert_eq!(image.get_pixel(8, 0), original.get_pixel(8, 0));
+        assert_eq!(image.get_pixel(8, 6), original.get_pixel(8, 6));
+    }
+}